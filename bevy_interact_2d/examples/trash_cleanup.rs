@@ -5,8 +5,10 @@ use bevy_interact_2d::InteractionDebugPlugin as InteractionPlugin;
 #[cfg(not(feature = "debug"))]
 use bevy_interact_2d::InteractionPlugin;
 use bevy_interact_2d::{
-  drag::{DragPlugin, Draggable, Dragged},
-  Group, Interactable, InteractionSource, InteractionState,
+  drag::{DragPlugin, Draggable, DropZone, Dropped},
+  hover::{HoverEnded, HoverStarted},
+  shape::Shape,
+  Group, Interactable, InteractionSource,
 };
 use rand::prelude::*;
 
@@ -72,7 +74,10 @@ fn setup(
     })
     .insert(Interactable {
       groups: vec![Group(crate::TRASHCAN_GROUP)],
-      bounding_box: (Vec2::new(-12., -12.), Vec2::new(12., 12.)),
+      shape: Shape::rect(Vec2::new(24., 24.)),
+    })
+    .insert(DropZone {
+      groups: vec![Group(TRASH_GROUP)],
       ..Default::default()
     })
     .insert(TrashCan {})
@@ -94,8 +99,7 @@ fn setup(
       })
       .insert(Interactable {
         groups: vec![Group(crate::TRASH_GROUP)],
-        bounding_box: (Vec2::new(-12., -12.), Vec2::new(12., 12.)),
-        ..Default::default()
+        shape: Shape::rect(Vec2::new(24., 24.)),
       })
       .insert(Draggable {
         groups: vec![Group(crate::TRASH_GROUP)],
@@ -118,24 +122,19 @@ fn setup(
 // This system opens and closes the trashcan when the mouse
 // hovers over it by changing the sprite index
 fn interact_with_trashcan(
-  interaction_state: Res<InteractionState>,
-  mut query: Query<(Entity, &mut TextureAtlasSprite), With<TrashCan>>,
+  mut hover_started: EventReader<HoverStarted>,
+  mut hover_ended: EventReader<HoverEnded>,
+  mut query: Query<&mut TextureAtlasSprite, With<TrashCan>>,
 ) {
-  for (entity, mut sprite) in query.iter_mut() {
-    if interaction_state
-      .get_group(Group(TRASHCAN_GROUP))
-      .iter()
-      .find(|(e, _)| *e == entity)
-      .is_some()
-    {
-      if sprite.index == 0 {
-        info!("Opening trashcan.");
-      }
+  for event in hover_started.iter() {
+    if let Ok(mut sprite) = query.get_mut(event.entity) {
+      info!("Opening trashcan.");
       sprite.index = 1;
-    } else {
-      if sprite.index == 1 {
-        info!("Closing trashcan.");
-      }
+    }
+  }
+  for event in hover_ended.iter() {
+    if let Ok(mut sprite) = query.get_mut(event.entity) {
+      info!("Closing trashcan.");
       sprite.index = 0;
     }
   }
@@ -143,27 +142,13 @@ fn interact_with_trashcan(
 
 fn drag_trash(
   mut commands: Commands,
-  mouse_button_input: Res<Input<MouseButton>>,
-  interaction_state: Res<InteractionState>,
-  dragged_trash_query: Query<Entity, (With<Dragged>, With<Trash>)>,
+  mut dropped: EventReader<Dropped>,
+  trash_query: Query<Entity, With<Trash>>,
 ) {
-  // We're only interested in the release of the left mouse button
-  if !mouse_button_input.just_released(MouseButton::Left) {
-    return;
-  }
-
-  // Because there's only one interaction source it's safe to assume
-  // that if the trashcan interaction group is not empty any drag that
-  // ended in this cycle was ended over a trashcan.
-  let over_trashcan = interaction_state.get_group(Group(TRASHCAN_GROUP)).len() > 0;
-
-  for dragged_trash in dragged_trash_query.iter() {
-    if over_trashcan {
+  for Dropped { dragged, .. } in dropped.iter() {
+    if trash_query.contains(*dragged) {
       info!("Removing trash.");
-      commands.entity(dragged_trash).despawn_recursive();
-    } else {
-      info!("Dropping trash.");
-      commands.entity(dragged_trash).remove::<Dragged>();
+      commands.entity(*dragged).despawn_recursive();
     }
   }
 }
@@ -0,0 +1,234 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use super::{virtual_cursor::VirtualCursors, Group, InteractionState};
+
+/// Sent when a button is pressed and released on the same entity
+/// without the cursor moving beyond the movement tolerance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clicked {
+  pub entity:   Entity,
+  pub group:    Group,
+  pub button:   MouseButton,
+  pub position: Vec2,
+}
+
+/// Sent together with the second of two clicks on the same entity
+/// within the double click interval.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoubleClicked {
+  pub entity:   Entity,
+  pub group:    Group,
+  pub button:   MouseButton,
+  pub position: Vec2,
+}
+
+/// Sent once a button has been held on an entity for the long press duration.
+/// A long press is not followed by a click when the button is released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LongPressed {
+  pub entity:   Entity,
+  pub group:    Group,
+  pub button:   MouseButton,
+  pub position: Vec2,
+}
+
+/// Configures how presses on interactable entities are turned into clicks
+#[derive(Resource)]
+pub struct ClickSettings {
+  /// The mouse buttons that can click
+  pub buttons:               Vec<MouseButton>,
+  /// Maximum time in seconds between two clicks to count as a double click
+  pub double_click_interval: f32,
+  /// Time in seconds a button has to be held to count as a long press
+  pub long_press_duration:   f32,
+  /// Distance in world units the cursor may move while pressed,
+  /// moving any further cancels the click so it can become a drag instead.
+  pub movement_tolerance:    f32,
+}
+
+impl Default for ClickSettings {
+  fn default() -> Self {
+    Self {
+      buttons:               vec![MouseButton::Left, MouseButton::Right, MouseButton::Middle],
+      double_click_interval: 0.3,
+      long_press_duration:   0.5,
+      movement_tolerance:    4.,
+    }
+  }
+}
+
+struct Press {
+  entity:         Entity,
+  group:          Group,
+  button:         MouseButton,
+  /// The interaction source whose virtual cursor pressed the entity
+  virtual_cursor: Option<Entity>,
+  position:       Vec2,
+  started:        f64,
+  long_pressed:   bool,
+}
+
+struct LastClick {
+  entity: Entity,
+  button: MouseButton,
+  time:   f64,
+}
+
+/// Presses that may still turn into clicks and recent clicks
+/// that may still turn into double clicks.
+#[derive(Default, Resource)]
+pub struct ClickState {
+  presses:     Vec<Press>,
+  last_clicks: Vec<LastClick>,
+}
+
+/// The writers for the click events
+#[derive(SystemParam)]
+pub struct ClickEvents<'w, 's> {
+  clicked:        EventWriter<'w, 's, Clicked>,
+  double_clicked: EventWriter<'w, 's, DoubleClicked>,
+  long_pressed:   EventWriter<'w, 's, LongPressed>,
+}
+
+/// The click plugin sends click, double click and long press events
+/// for the topmost interactable entity under the cursor in each group.
+pub struct ClickPlugin;
+impl Plugin for ClickPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<InteractionState>()
+      .init_resource::<ClickSettings>()
+      .init_resource::<ClickState>()
+      .add_event::<Clicked>()
+      .add_event::<DoubleClicked>()
+      .add_event::<LongPressed>()
+      .add_system(click_system);
+  }
+}
+
+/// This system tracks presses on interactable entities and sends
+/// the click events when they are held or released.
+/// Presses of a virtual cursor count as the left mouse button,
+/// but only in the groups of its interaction source.
+pub fn click_system(
+  time: Res<Time>,
+  settings: Res<ClickSettings>,
+  interaction_state: Res<InteractionState>,
+  mouse_button_input: Res<Input<MouseButton>>,
+  virtual_cursors: VirtualCursors,
+  mut click_state: ResMut<ClickState>,
+  mut events: ClickEvents,
+) {
+  let now = time.elapsed_seconds_f64();
+  let click_state = &mut *click_state;
+
+  let presses: Vec<(MouseButton, Option<Entity>)> = mouse_button_input
+    .get_just_pressed()
+    .map(|button| (*button, None))
+    .chain(
+      virtual_cursors
+        .just_pressed()
+        .map(|source| (MouseButton::Left, Some(source))),
+    )
+    .collect();
+  for (button, virtual_cursor) in presses {
+    if !settings.buttons.contains(&button) {
+      continue;
+    }
+    for group in interaction_state.ordered_interact_list_map.keys() {
+      if virtual_cursors.get_group(*group).map(|(source, _)| source) != virtual_cursor {
+        continue;
+      }
+      if let Some((entity, position)) = interaction_state.topmost(*group) {
+        click_state.presses.push(Press {
+          entity,
+          group: *group,
+          button,
+          virtual_cursor,
+          position,
+          started: now,
+          long_pressed: false,
+        });
+      }
+    }
+  }
+
+  // Presses where the cursor moved too far have become drags
+  click_state.presses.retain(
+    |press| match interaction_state.cursor_positions.get(&press.group) {
+      Some(position) => position.distance(press.position) <= settings.movement_tolerance,
+      None => false,
+    },
+  );
+
+  for press in click_state.presses.iter_mut() {
+    if !press.long_pressed && now - press.started >= settings.long_press_duration as f64 {
+      press.long_pressed = true;
+      events.long_pressed.send(LongPressed {
+        entity:   press.entity,
+        group:    press.group,
+        button:   press.button,
+        position: press.position,
+      });
+    }
+  }
+
+  click_state
+    .last_clicks
+    .retain(|click| now - click.time <= settings.double_click_interval as f64);
+
+  // Presses of a virtual cursor that has been removed count as released
+  let (released, pending): (Vec<Press>, Vec<Press>) =
+    click_state
+      .presses
+      .drain(..)
+      .partition(|press| match press.virtual_cursor {
+        Some(source) => virtual_cursors
+          .get(source)
+          .is_none_or(|cursor| cursor.just_released()),
+        None => mouse_button_input.just_released(press.button),
+      });
+  click_state.presses = pending;
+
+  for press in released {
+    let still_over = interaction_state
+      .get_group(press.group)
+      .iter()
+      .any(|(entity, _)| *entity == press.entity);
+    if press.long_pressed || !still_over {
+      continue;
+    }
+    let position = interaction_state
+      .cursor_positions
+      .get(&press.group)
+      .copied()
+      .unwrap_or(press.position);
+    events.clicked.send(Clicked {
+      entity: press.entity,
+      group: press.group,
+      button: press.button,
+      position,
+    });
+
+    let previous_click = click_state
+      .last_clicks
+      .iter()
+      .position(|click| click.entity == press.entity && click.button == press.button);
+    match previous_click {
+      Some(index) => {
+        click_state.last_clicks.remove(index);
+        events.double_clicked.send(DoubleClicked {
+          entity: press.entity,
+          group: press.group,
+          button: press.button,
+          position,
+        });
+      }
+      None => click_state.last_clicks.push(LastClick {
+        entity: press.entity,
+        button: press.button,
+        time:   now,
+      }),
+    }
+  }
+}
@@ -1,22 +1,173 @@
-use bevy::prelude::*;
+use bevy::{
+  ecs::system::SystemParam,
+  input::InputSystem,
+  prelude::*,
+  render::camera::RenderTarget,
+  window::{CursorLeft, WindowFocused},
+};
 
-use super::{Group, Interactable, InteractionState};
+use super::{
+  interaction_system,
+  select::Selected,
+  topmost_first,
+  virtual_cursor::{VirtualCursor, VirtualCursors},
+  Group, Interactable, InteractionPriority, InteractionSource, InteractionState,
+};
 
 #[derive(Component)]
 pub struct Dragged {
-  pub group:        Group,
-  pub translation:  Vec2,
-  pub origin:       Vec2,
-  pub just_dropped: bool,
-  pub just_dragged: bool,
+  pub group:          Group,
+  pub translation:    Vec2,
+  /// World position when the drag started
+  pub origin:         Vec2,
+  /// Translation of the `Transform` when the drag started, relative to the parent
+  pub local_origin:   Vec3,
+  /// The touch dragging the entity, or None when dragged with the mouse
+  pub touch_id:       Option<u64>,
+  /// The mouse button that started the drag,
+  /// or None when dragged by touch or by a virtual cursor
+  pub button:         Option<MouseButton>,
+  /// The interaction source whose virtual cursor started the drag
+  pub virtual_cursor: Option<Entity>,
+  /// The ghost following the cursor instead of the entity in the ghost drag modes
+  pub ghost:          Option<Entity>,
+  /// The z of the translation while dragging when raised to a drag layer
+  pub drag_z:         Option<f32>,
+  /// The translation the entity is dragged to, relative to the parent.
+  /// The entity only moves there when dropped if it has a ghost.
+  target:             Vec3,
+  /// Recent world positions of the entity with the elapsed time they were reached at
+  samples:            Vec<(f64, Vec2)>,
 }
 
+/// Marks a ghost following the cursor in place of the dragged entity
+#[derive(Component)]
+pub struct DragGhost {
+  pub source: Entity,
+}
+
+/// Time in seconds over which the velocity of a dragged entity is measured
+const VELOCITY_WINDOW: f64 = 0.1;
+
 impl Dragged {
-  pub fn just_dropped(&self) -> bool {
-    self.just_dropped
+  /// Velocity of the dragged entity in world units per second
+  pub fn velocity(&self) -> Vec2 {
+    match (self.samples.first(), self.samples.last()) {
+      (Some((first_time, first)), Some((last_time, last))) if last_time > first_time => {
+        (*last - *first) / (last_time - first_time) as f32
+      }
+      _ => Vec2::ZERO,
+    }
   }
-  pub fn just_dragged(&self) -> bool {
-    self.just_dragged
+}
+
+/// A press on a draggable that has not moved beyond the drag threshold
+/// or has not been held for the drag delay yet.
+/// Released pending drags never become drags, so they count as clicks.
+#[derive(Component)]
+pub struct PendingDrag {
+  pub group:          Group,
+  /// Where the press started in world coordinates
+  pub position:       Vec2,
+  pub touch_id:       Option<u64>,
+  pub button:         Option<MouseButton>,
+  /// The interaction source whose virtual cursor pressed the draggable
+  pub virtual_cursor: Option<Entity>,
+  /// Elapsed time in seconds when the press started
+  pub started:        f64,
+}
+
+/// Sent when an entity starts being dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragStarted {
+  pub entity: Entity,
+  pub group:  Group,
+}
+
+/// Sent whenever a dragged entity moves, the delta is in world units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragMoved {
+  pub entity: Entity,
+  pub delta:  Vec2,
+}
+
+/// Sent when a drag ends by releasing the entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEnded {
+  pub entity: Entity,
+}
+
+/// Sent when an animated drop strategy has finished moving the entity
+/// and the entity is no longer dragged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DropAnimationFinished {
+  pub entity: Entity,
+}
+
+/// Sent when a flung entity has come to rest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlingStopped {
+  pub entity: Entity,
+}
+
+/// Sent when a drag is cancelled and the entity returned to where the drag started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragCancelled {
+  pub entity: Entity,
+}
+
+/// Labels for the phases of dragging, so systems can be ordered around them.
+/// Drags are started, then dragged entities are moved, then drags are stopped.
+/// Drags are cancelled in the pre update stage, before any of the other phases.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum DragSystem {
+  Cancel,
+  Start,
+  Drag,
+  Stop,
+}
+
+/// Send to cancel drags, returning the entities to where their drag started
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CancelDrag {
+  /// The dragged entity to cancel, or None to cancel every drag
+  pub entity: Option<Entity>,
+}
+
+/// Receives the draggables under the cursor, topmost first, and returns the ones to drag
+pub type PickFilter = Box<dyn Fn(&[Entity]) -> Vec<Entity> + Send + Sync>;
+
+/// Decides which of the draggables under the cursor start being dragged on a press
+pub enum PickPolicy {
+  /// Only the topmost draggable is dragged
+  Topmost,
+  /// Every draggable under the cursor is dragged
+  All,
+  /// The filter receives the draggables under the cursor, topmost first,
+  /// and returns the ones to drag.
+  Custom(PickFilter),
+}
+
+/// Configures the drag plugin
+#[derive(Resource)]
+pub struct DragSettings {
+  pub pick_policy:           PickPolicy,
+  /// Keys that cancel every drag
+  pub cancel_keys:           Vec<KeyCode>,
+  /// Cancel every drag when a window loses focus
+  pub cancel_on_focus_loss:  bool,
+  /// Cancel mouse drags when the cursor leaves a window
+  pub cancel_on_cursor_left: bool,
+}
+
+impl Default for DragSettings {
+  fn default() -> Self {
+    Self {
+      pick_policy:           PickPolicy::Topmost,
+      cancel_keys:           vec![KeyCode::Escape],
+      cancel_on_focus_loss:  true,
+      cancel_on_cursor_left: true,
+    }
   }
 }
 
@@ -25,37 +176,585 @@ impl Plugin for DragPlugin {
   fn build(&self, app: &mut App) {
     app
       .init_resource::<InteractionState>()
-      .add_system(mouse_press_start_drag_system)
-      .add_system(mouse_release_stop_drag_system)
-      .add_system(drag_system);
+      .init_resource::<DragSettings>()
+      .add_event::<DragStarted>()
+      .add_event::<DragMoved>()
+      .add_event::<DragEnded>()
+      .add_event::<DragCancelled>()
+      .add_event::<DropAnimationFinished>()
+      .add_event::<CancelDrag>()
+      .add_event::<FlingStopped>()
+      .add_event::<Dropped>()
+      .add_event::<DropRejected>()
+      .add_system_to_stage(
+        CoreStage::PreUpdate,
+        cancel_drag_system
+          .label(DragSystem::Cancel)
+          .after(InputSystem),
+      )
+      .add_system(mouse_press_start_drag_system.label(DragSystem::Start))
+      .add_system(
+        pending_drag_system
+          .label(DragSystem::Start)
+          .after(mouse_press_start_drag_system),
+      )
+      // A touch is only known to the interaction state in the frame it is pressed
+      // once the interaction system has run, so touch drags start right after it.
+      .add_system_to_stage(
+        CoreStage::PostUpdate,
+        touch_press_start_drag_system
+          .label(DragSystem::Start)
+          .after(interaction_system),
+      )
+      .add_system(
+        edge_scroll_system
+          .label(DragSystem::Drag)
+          .after(DragSystem::Start)
+          .before(drag_system),
+      )
+      .add_system(drag_system.label(DragSystem::Drag).after(DragSystem::Start))
+      .add_system(
+        fling_system
+          .label(DragSystem::Drag)
+          .after(DragSystem::Start),
+      )
+      .add_system(
+        drop_animation_system
+          .label(DragSystem::Drag)
+          .after(DragSystem::Start),
+      )
+      .add_system(
+        mouse_release_stop_drag_system
+          .label(DragSystem::Stop)
+          .after(DragSystem::Drag),
+      )
+      .add_system(
+        touch_release_stop_drag_system
+          .label(DragSystem::Stop)
+          .after(DragSystem::Drag),
+      );
   }
 }
 
+/// Moves dragged entities with their cursor or touch,
+/// applying the constraints of the draggable.
 pub fn drag_system(
+  time: Res<Time>,
   interaction_state: Res<InteractionState>,
-  mut draggables: Query<(&mut Transform, &mut Dragged, &GlobalTransform)>,
+  mut draggables: Query<
+    (
+      Entity,
+      &mut Transform,
+      &Draggable,
+      &mut Dragged,
+      &GlobalTransform,
+    ),
+    Without<DropAnimating>,
+  >,
+  mut ghosts: Query<&mut Transform, (With<DragGhost>, Without<Dragged>)>,
+  mut drag_moved: EventWriter<DragMoved>,
+  mut drag_stop: DragStop,
 ) {
-  for (mut transform, mut dragged, global_transform) in draggables.iter_mut() {
-    if dragged.just_dragged {
-      dragged.just_dragged = false;
-    }
-    if let Some(cursor_position) = interaction_state.cursor_positions.get(&dragged.group) {
+  let now = time.elapsed_seconds_f64();
+  for (entity, mut transform, draggable, mut dragged, global_transform) in draggables.iter_mut() {
+    if let Some(cursor_position) = interaction_state.position(dragged.group, dragged.touch_id) {
       let parent_matrix = global_transform
         .compute_matrix()
         .mul_mat4(&transform.compute_matrix().inverse());
-      let global_hook_translation =
-        (*cursor_position + dragged.translation).extend(transform.translation.z);
+      let global_hook_translation = draggable
+        .constraints
+        .iter()
+        .fold(
+          cursor_position + dragged.translation,
+          |position, constraint| constraint.constrain(position, dragged.origin),
+        )
+        .extend(transform.translation.z);
 
-      transform.translation = parent_matrix
+      let mut translation = parent_matrix
         .inverse()
         .transform_point3(global_hook_translation);
+      translation.z = dragged.drag_z.unwrap_or(transform.translation.z);
+      for constraint in draggable.constraints.iter() {
+        if let DragConstraint::ClampLocal(rect) = constraint {
+          translation = translation
+            .truncate()
+            .clamp(rect.min, rect.max)
+            .extend(translation.z);
+        }
+      }
+      if let Some(snap) = &draggable.snap {
+        if snap.mode == SnapMode::WhileDragging {
+          translation = snap.snap(translation.truncate()).extend(translation.z);
+        }
+      }
+
+      let world_translation = parent_matrix.transform_point3(translation);
+      let ghost = match dragged.ghost {
+        Some(ghost) => match ghosts.get_mut(ghost) {
+          Ok(ghost_transform) => Some(ghost_transform),
+          // The ghost was despawned from outside the drag, the original stays in its slot
+          Err(_) => {
+            dragged.ghost = None;
+            drag_stop.cancel(entity, &dragged, &mut transform);
+            continue;
+          }
+        },
+        None => None,
+      };
+      let current = match &ghost {
+        Some(ghost_transform) => ghost_transform.translation.truncate(),
+        None => global_transform.translation().truncate(),
+      };
+      let delta = world_translation.truncate() - current;
+      if delta != Vec2::ZERO {
+        drag_moved.send(DragMoved { entity, delta });
+      }
+
+      dragged.samples.push((now, world_translation.truncate()));
+      dragged
+        .samples
+        .retain(|(time, _)| now - *time <= VELOCITY_WINDOW);
+
+      dragged.target = translation;
+      match ghost {
+        Some(mut ghost_transform) => {
+          ghost_transform.translation = world_translation;
+        }
+        None => transform.translation = translation,
+      }
+    }
+  }
+}
+
+/// Spawns a ghost for a draggable with the given world transform and returns the ghost
+pub type GhostSpawner = Box<dyn Fn(&mut Commands, Entity, Transform) -> Entity + Send + Sync>;
+
+/// Decides what follows the cursor while dragging
+pub enum DragMode {
+  /// Move the draggable itself
+  Move,
+  /// Leave the draggable in place and drag a copy of its sprite or texture atlas sprite,
+  /// with the color of the sprite multiplied by the tint.
+  /// Draggables without a sprite are moved instead, with a warning.
+  Ghost { tint: Color },
+  /// Leave the draggable in place and drag the entity spawned by the callback.
+  /// The callback receives the draggable and the world transform to spawn the ghost with.
+  CustomGhost(GhostSpawner),
+}
+
+/// Where a dragged entity is raised to while dragging
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragLayer {
+  /// Raise the entity to this z in world coordinates
+  Z(f32),
+  /// Raise the entity by this offset to the z of its `Transform`
+  Offset(f32),
+}
+
+impl DragLayer {
+  /// The z of the translation of the entity that puts it on the drag layer
+  pub fn local_z(&self, transform: &Transform, global_transform: &GlobalTransform) -> f32 {
+    match self {
+      DragLayer::Z(z) => {
+        let parent_matrix = global_transform
+          .compute_matrix()
+          .mul_mat4(&transform.compute_matrix().inverse());
+        let base = parent_matrix
+          .transform_point3(transform.translation.truncate().extend(0.))
+          .z;
+        let scale = parent_matrix.z_axis.z;
+        if scale == 0. {
+          transform.translation.z
+        } else {
+          (*z - base) / scale
+        }
+      }
+      DragLayer::Offset(offset) => transform.translation.z + offset,
+    }
+  }
+}
+
+/// Pans an interaction source camera while an entity is dragged with the mouse
+/// near the edge of its viewport, so it can be carried beyond the visible area.
+#[derive(Component)]
+pub struct EdgeScroll {
+  /// Distance in logical pixels from the edge of the viewport where scrolling starts
+  pub margin:    f32,
+  /// Speed in world units per second with the cursor at the edge of the viewport
+  pub max_speed: f32,
+  /// How the speed grows from the inner border of the margin to the edge of the viewport
+  pub curve:     Easing,
+  /// World bounds the camera position is kept inside
+  pub bounds:    Option<Rect>,
+}
+
+impl Default for EdgeScroll {
+  fn default() -> Self {
+    Self {
+      margin:    32.,
+      max_speed: 500.,
+      curve:     Easing::EaseIn,
+      bounds:    None,
+    }
+  }
+}
+
+impl EdgeScroll {
+  /// Velocity of the camera in world units per second
+  /// with the cursor at the given position in a viewport of the given size
+  pub fn velocity(&self, position: Vec2, viewport_size: Vec2) -> Vec2 {
+    let axis = |position: f32, size: f32| {
+      let low = position / self.margin;
+      let high = (size - position) / self.margin;
+      if low < 1. {
+        -self.curve.ease((1. - low).min(1.))
+      } else if high < 1. {
+        self.curve.ease((1. - high).min(1.))
+      } else {
+        0.
+      }
+    };
+    Vec2::new(
+      axis(position.x, viewport_size.x),
+      axis(position.y, viewport_size.y),
+    ) * self.max_speed
+  }
+}
+
+/// Pans cameras with edge scrolling while one of their groups drags an entity with the mouse,
+/// moving the cursor positions of their groups along so dragged entities stay under the cursor.
+pub fn edge_scroll_system(
+  time: Res<Time>,
+  windows: Res<Windows>,
+  mut interaction_state: ResMut<InteractionState>,
+  dragged: Query<&Dragged, Without<DropAnimating>>,
+  mut cameras: Query<(
+    &InteractionSource,
+    &EdgeScroll,
+    &Camera,
+    &mut Transform,
+    Option<&VirtualCursor>,
+  )>,
+) {
+  for (source, edge_scroll, camera, mut transform, virtual_cursor) in cameras.iter_mut() {
+    let dragging = dragged
+      .iter()
+      .any(|dragged| dragged.touch_id.is_none() && source.groups.contains(&dragged.group));
+    if !dragging {
+      continue;
+    }
+    let window_id = match camera.target {
+      RenderTarget::Window(window_id) => window_id,
+      RenderTarget::Image(_) => continue,
+    };
+    let window = match windows.get(window_id) {
+      Some(window) => window,
+      None => continue,
+    };
+    let position = match virtual_cursor {
+      Some(virtual_cursor) => virtual_cursor.position,
+      None => match interaction_state.window_cursor_positions.get(&window_id) {
+        Some(position) => *position,
+        None => continue,
+      },
+    };
+
+    // The margin is measured from the edges of the viewport, which is measured from
+    // the top left of the window while the cursor is measured from the bottom left.
+    let window_size = Vec2::new(window.width(), window.height());
+    let (viewport_min, viewport_max) = camera
+      .logical_viewport_rect()
+      .unwrap_or((Vec2::ZERO, window_size));
+    let viewport_position = Vec2::new(
+      position.x - viewport_min.x,
+      position.y - (window_size.y - viewport_max.y),
+    );
+    let viewport_size = viewport_max - viewport_min;
+    // The cursor is in the viewport of another camera
+    if viewport_position.cmplt(Vec2::ZERO).any() || viewport_position.cmpge(viewport_size).any() {
+      continue;
+    }
+
+    let velocity = edge_scroll.velocity(viewport_position, viewport_size);
+    let mut translation = transform.translation.truncate() + velocity * time.delta_seconds();
+    if let Some(bounds) = edge_scroll.bounds {
+      translation = translation.clamp(bounds.min, bounds.max);
+    }
+    let delta = translation - transform.translation.truncate();
+    if delta == Vec2::ZERO {
+      continue;
+    }
+    transform.translation = translation.extend(transform.translation.z);
+
+    // The world position under the cursor moves along with the camera
+    for group in source.groups.iter() {
+      if let Some(cursor_position) = interaction_state.cursor_positions.get_mut(group) {
+        *cursor_position += delta;
+      }
+    }
+  }
+}
+
+/// Restricts where a dragged entity can go
+pub enum DragConstraint {
+  /// Only move horizontally, keeping the y coordinate the drag started at
+  Horizontal,
+  /// Only move vertically, keeping the x coordinate the drag started at
+  Vertical,
+  /// Stay inside a rect in world coordinates
+  Clamp(Rect),
+  /// Stay inside a rect in the coordinates of the parent
+  ClampLocal(Rect),
+  /// Stay on the line segment between two points in world coordinates
+  Segment(Vec2, Vec2),
+  /// Stay on a circle in world coordinates
+  Circle { center: Vec2, radius: f32 },
+  /// Map the wanted world position to a position on a custom path
+  Path(Box<dyn Fn(Vec2) -> Vec2 + Send + Sync>),
+}
+
+impl DragConstraint {
+  /// Constrains a world position, given where the drag started.
+  /// Local constraints are applied separately and leave the position as is.
+  pub fn constrain(&self, position: Vec2, origin: Vec2) -> Vec2 {
+    match self {
+      DragConstraint::Horizontal => Vec2::new(position.x, origin.y),
+      DragConstraint::Vertical => Vec2::new(origin.x, position.y),
+      DragConstraint::Clamp(rect) => position.clamp(rect.min, rect.max),
+      DragConstraint::ClampLocal(_) => position,
+      DragConstraint::Segment(a, b) => {
+        let segment = *b - *a;
+        let length_squared = segment.length_squared();
+        if length_squared == 0. {
+          return *a;
+        }
+        let t = ((position - *a).dot(segment) / length_squared).clamp(0., 1.);
+        *a + segment * t
+      }
+      DragConstraint::Circle { center, radius } => {
+        let direction = (position - *center).try_normalize().unwrap_or(Vec2::X);
+        *center + direction * *radius
+      }
+      DragConstraint::Path(path) => path(position),
+    }
+  }
+}
+
+/// Where dragged entities snap to, in the coordinates of their parent
+pub enum SnapTarget {
+  /// The centers of the cells are offset from the origin by `offset`
+  Grid { cell_size: Vec2, offset: Vec2 },
+  /// The nearest of the anchor points
+  Anchors(Vec<Vec2>),
+}
+
+/// When dragged entities snap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapMode {
+  /// Snap every frame while dragging
+  WhileDragging,
+  /// Move freely and only snap when dropped
+  OnDrop,
+}
+
+/// Snaps a draggable to a grid or anchor points
+pub struct Snap {
+  pub target: SnapTarget,
+  pub mode:   SnapMode,
+}
+
+impl Snap {
+  /// Snaps a translation in the coordinates of the parent
+  pub fn snap(&self, translation: Vec2) -> Vec2 {
+    match &self.target {
+      SnapTarget::Grid { cell_size, offset } => {
+        let cells = (translation - *offset) / *cell_size;
+        let snapped = cells.round() * *cell_size + *offset;
+        // Axes with a cell size of zero don't snap
+        Vec2::select(cell_size.cmpeq(Vec2::ZERO), translation, snapped)
+      }
+      SnapTarget::Anchors(anchors) => anchors
+        .iter()
+        .copied()
+        .min_by(|a, b| {
+          a.distance_squared(translation)
+            .total_cmp(&b.distance_squared(translation))
+        })
+        .unwrap_or(translation),
+    }
+  }
+}
+
+/// A modifier key, either the left or the right key counts as held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+  Shift,
+  Control,
+  Alt,
+  Super,
+}
+
+impl Modifier {
+  pub fn keys(&self) -> [KeyCode; 2] {
+    match self {
+      Modifier::Shift => [KeyCode::LShift, KeyCode::RShift],
+      Modifier::Control => [KeyCode::LControl, KeyCode::RControl],
+      Modifier::Alt => [KeyCode::LAlt, KeyCode::RAlt],
+      Modifier::Super => [KeyCode::LWin, KeyCode::RWin],
+    }
+  }
+
+  pub fn held(&self, keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.any_pressed(self.keys())
+  }
+}
+
+/// Easing curves for drop animations
+#[derive(Debug, Clone, Copy)]
+pub enum Easing {
+  Linear,
+  EaseIn,
+  EaseOut,
+  EaseInOut,
+  /// Maps the progress from 0 to 1 to the eased progress
+  Custom(fn(f32) -> f32),
+}
+
+impl Easing {
+  pub fn ease(&self, t: f32) -> f32 {
+    match self {
+      Easing::Linear => t,
+      Easing::EaseIn => t * t,
+      Easing::EaseOut => 1. - (1. - t) * (1. - t),
+      Easing::EaseInOut => {
+        if t < 0.5 {
+          2. * t * t
+        } else {
+          1. - (-2. * t + 2.).powi(2) / 2.
+        }
+      }
+      Easing::Custom(ease) => ease(t),
+    }
+  }
+}
+
+/// How a dropped entity is animated to where the drop strategy puts it
+#[derive(Debug, Clone, Copy)]
+pub struct DropAnimation {
+  /// Duration in seconds
+  pub duration: f32,
+  pub easing:   Easing,
+}
+
+impl Default for DropAnimation {
+  fn default() -> Self {
+    Self {
+      duration: 0.2,
+      easing:   Easing::EaseOut,
     }
   }
 }
 
 pub enum DropStrategy {
+  /// Return to where the drag started
   Reset,
+  /// Stay where the entity was dropped
   Leave,
+  /// Stay where the entity was dropped if a drop zone accepts it,
+  /// otherwise return to where the drag started
+  ResetUnlessAccepted,
+  /// Like `Reset`, easing back to where the drag started
+  AnimatedReset(DropAnimation),
+  /// Like `ResetUnlessAccepted`, easing back to where the drag started
+  AnimatedResetUnlessAccepted(DropAnimation),
+  /// Like `Leave`, easing into the snap target of the draggable if it has one
+  AnimatedSettle(DropAnimation),
+  /// Keep moving with the velocity the entity was dropped with until it comes to rest.
+  /// Friction is the deceleration in world units per second squared,
+  /// the max speed in world units per second.
+  Fling { friction: f32, max_speed: f32 },
+}
+
+impl DropStrategy {
+  fn animation(&self) -> Option<DropAnimation> {
+    match self {
+      DropStrategy::AnimatedReset(animation)
+      | DropStrategy::AnimatedResetUnlessAccepted(animation)
+      | DropStrategy::AnimatedSettle(animation) => Some(*animation),
+      _ => None,
+    }
+  }
+}
+
+/// An entity flung by the `Fling` drop strategy, moving until it comes to rest
+#[derive(Component)]
+pub struct Flung {
+  /// Velocity in world units per second
+  pub velocity: Vec2,
+  pub friction: f32,
+}
+
+/// Moves flung entities and slows them down until they come to rest
+pub fn fling_system(
+  time: Res<Time>,
+  mut flung: Query<(Entity, &mut Transform, &GlobalTransform, &mut Flung)>,
+  mut fling_stopped: EventWriter<FlingStopped>,
+  mut commands: Commands,
+) {
+  for (entity, mut transform, global_transform, mut flung) in flung.iter_mut() {
+    let parent_matrix = global_transform
+      .compute_matrix()
+      .mul_mat4(&transform.compute_matrix().inverse());
+    let step = flung.velocity * time.delta_seconds();
+    transform.translation += parent_matrix.inverse().transform_vector3(step.extend(0.));
+
+    let speed = flung.velocity.length() - flung.friction * time.delta_seconds();
+    if speed > 0. {
+      flung.velocity = flung.velocity.normalize_or_zero() * speed;
+    } else {
+      commands.entity(entity).remove::<Flung>();
+      fling_stopped.send(FlingStopped { entity });
+    }
+  }
+}
+
+/// A dropped entity being animated by its drop strategy.
+/// The entity stays dragged until the animation has finished.
+#[derive(Component)]
+pub struct DropAnimating {
+  pub from:      Vec3,
+  pub to:        Vec3,
+  /// Elapsed time in seconds when the animation started
+  pub started:   f64,
+  pub animation: DropAnimation,
+}
+
+/// Moves entities animated by their drop strategy and stops dragging them
+/// once the animation has finished.
+pub fn drop_animation_system(
+  time: Res<Time>,
+  mut animating: Query<(Entity, &mut Transform, &DropAnimating)>,
+  mut finished: EventWriter<DropAnimationFinished>,
+  mut commands: Commands,
+) {
+  for (entity, mut transform, animating) in animating.iter_mut() {
+    let elapsed = (time.elapsed_seconds_f64() - animating.started) as f32;
+    let t = if animating.animation.duration > 0. {
+      (elapsed / animating.animation.duration).min(1.)
+    } else {
+      1.
+    };
+    transform.translation = animating
+      .from
+      .lerp(animating.to, animating.animation.easing.ease(t));
+    if t >= 1. {
+      commands
+        .entity(entity)
+        .remove::<DropAnimating>()
+        .remove::<Dragged>();
+      finished.send(DropAnimationFinished { entity });
+    }
+  }
 }
 
 #[derive(Component)]
@@ -66,6 +765,24 @@ pub struct Draggable {
   pub hook:          Option<Vec2>,
   pub groups:        Vec<Group>,
   pub drop_strategy: DropStrategy,
+  /// Tags drop zones can accept the draggable by
+  pub tags:          Vec<String>,
+  /// The mouse buttons that start a drag
+  pub buttons:       Vec<MouseButton>,
+  /// Modifiers that all have to be held for a mouse press to start a drag
+  pub modifiers:     Vec<Modifier>,
+  /// Distance in world units the cursor has to move after a press
+  /// before the press turns into a drag
+  pub threshold:     f32,
+  /// Time in seconds a press has to be held before it turns into a drag
+  pub delay:         f32,
+  /// Applied in order every frame while dragging
+  pub constraints:   Vec<DragConstraint>,
+  pub snap:          Option<Snap>,
+  pub mode:          DragMode,
+  /// Raises the entity while dragging, so it is drawn on top and is the topmost hit target.
+  /// The z of the entity is restored when the drag ends.
+  pub drag_layer:    Option<DragLayer>,
 }
 
 impl Default for Draggable {
@@ -74,56 +791,957 @@ impl Default for Draggable {
       hook:          None,
       groups:        vec![Group::default()],
       drop_strategy: DropStrategy::Leave,
+      tags:          vec![],
+      buttons:       vec![MouseButton::Left],
+      modifiers:     vec![],
+      threshold:     4.,
+      delay:         0.,
+      constraints:   vec![],
+      snap:          None,
+      mode:          DragMode::Move,
+      drag_layer:    None,
     }
   }
 }
 
+/// Marks an interactable entity as a place draggables can be dropped on
+#[derive(Component, Default)]
+pub struct DropZone {
+  /// Draggables dragged in one of these groups are accepted
+  pub groups: Vec<Group>,
+  /// Draggables with one of these tags are accepted
+  pub tags:   Vec<String>,
+}
+
+impl DropZone {
+  /// Whether the zone accepts a draggable dragged in the group with the given tags.
+  /// A zone without any groups or tags accepts every draggable.
+  pub fn accepts(&self, group: Group, tags: &[String]) -> bool {
+    (self.groups.is_empty() && self.tags.is_empty())
+      || self.groups.contains(&group)
+      || tags.iter().any(|tag| self.tags.contains(tag))
+  }
+}
+
+/// Sent when a drag ends over a drop zone that accepts the dragged entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dropped {
+  pub dragged: Entity,
+  pub zone:    Entity,
+}
+
+/// Sent when a drag ends over a drop zone that does not accept the dragged entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DropRejected {
+  pub dragged: Entity,
+  pub zone:    Entity,
+}
+
+/// Finds the drop zone a drag ended on and sends the drop events
+#[derive(SystemParam)]
+pub struct DropZones<'w, 's> {
+  zones: Query<
+    'w,
+    's,
+    (
+      &'static DropZone,
+      &'static GlobalTransform,
+      Option<&'static InteractionPriority>,
+    ),
+  >,
+  sources:  Query<'w, 's, &'static InteractionSource>,
+  dropped:  EventWriter<'w, 's, Dropped>,
+  rejected: EventWriter<'w, 's, DropRejected>,
+}
+
+impl<'w, 's> DropZones<'w, 's> {
+  /// Drops the entity on the topmost drop zone under its cursor or touch
+  /// in the groups of the interaction source it is dragged from.
+  /// Returns whether the drop was accepted by a drop zone.
+  pub fn drop(
+    &mut self,
+    interaction_state: &InteractionState,
+    entity: Entity,
+    draggable: &Draggable,
+    dragged: &Dragged,
+  ) -> bool {
+    // Only the groups of the interaction source the entity is dragged from share its pointer
+    let groups = self
+      .sources
+      .iter()
+      .find(|source| source.groups.contains(&dragged.group))
+      .map_or(std::slice::from_ref(&dragged.group), |source| {
+        source.groups.as_slice()
+      });
+    let lists: Vec<&Vec<(Entity, Vec2)>> = match dragged.touch_id {
+      Some(touch_id) => groups
+        .iter()
+        .filter_map(|group| interaction_state.touch_interact_list_map.get(group))
+        .filter_map(|touches| touches.get(&touch_id))
+        .collect(),
+      None => groups
+        .iter()
+        .filter_map(|group| interaction_state.ordered_interact_list_map.get(group))
+        .collect(),
+    };
+    // The lists of the groups are already ordered topmost first,
+    // only the topmost zones of the groups are compared with each other.
+    let zones = &self.zones;
+    let topmost_zone = lists
+      .into_iter()
+      .filter_map(|list| {
+        list
+          .iter()
+          .map(|(zone, _)| *zone)
+          .filter(|zone| *zone != entity)
+          .find_map(|zone| Some((zone, zones.get(zone).ok()?)))
+      })
+      .min_by(|(a, (_, a_transform, a_priority)), (b, (_, b_transform, b_priority))| {
+        topmost_first(
+          (*a, a_transform.translation().z, a_priority.copied().unwrap_or_default()),
+          (*b, b_transform.translation().z, b_priority.copied().unwrap_or_default()),
+        )
+      })
+      .map(|(zone, (drop_zone, _, _))| (zone, drop_zone));
+
+    match topmost_zone {
+      Some((zone, drop_zone)) if drop_zone.accepts(dragged.group, &draggable.tags) => {
+        self.dropped.send(Dropped {
+          dragged: entity,
+          zone,
+        });
+        true
+      }
+      Some((zone, _)) => {
+        self.rejected.send(DropRejected {
+          dragged: entity,
+          zone,
+        });
+        false
+      }
+      None => false,
+    }
+  }
+}
+
+/// Ends and cancels drags, applying the drop strategy and sending the drag events
+#[derive(SystemParam)]
+pub struct DragStop<'w, 's> {
+  interaction_state: Res<'w, InteractionState>,
+  drop_zones:        DropZones<'w, 's>,
+  drag_ended:        EventWriter<'w, 's, DragEnded>,
+  drag_cancelled:    EventWriter<'w, 's, DragCancelled>,
+  commands:          Commands<'w, 's>,
+  time:              Res<'w, Time>,
+}
+
+impl<'w, 's> DragStop<'w, 's> {
+  /// Drops the entity on the drop zone under its cursor or touch
+  /// and applies the drop strategy of the draggable.
+  pub fn end(
+    &mut self,
+    entity: Entity,
+    draggable: &Draggable,
+    dragged: &Dragged,
+    transform: &mut Transform,
+  ) {
+    let accepted = self
+      .drop_zones
+      .drop(&self.interaction_state, entity, draggable, dragged);
+    // Entities dragged by a ghost only move to where the ghost was dropped
+    // when a drop zone accepts them, otherwise they stay where they are
+    if let Some(ghost) = dragged.ghost {
+      if let Some(ghost) = self.commands.get_entity(ghost) {
+        ghost.despawn_recursive();
+      }
+      if !accepted {
+        transform.translation = dragged.local_origin;
+        self.commands.entity(entity).remove::<Dragged>();
+        self.drag_ended.send(DragEnded { entity });
+        return;
+      }
+      transform.translation = dragged.target;
+    }
+    transform.translation.z = dragged.local_origin.z;
+    let target = match (&draggable.drop_strategy, accepted) {
+      (DropStrategy::Reset, _)
+      | (DropStrategy::AnimatedReset(_), _)
+      | (DropStrategy::ResetUnlessAccepted, false)
+      | (DropStrategy::AnimatedResetUnlessAccepted(_), false) => dragged.local_origin,
+      _ => match &draggable.snap {
+        Some(snap) => snap
+          .snap(transform.translation.truncate())
+          .extend(transform.translation.z),
+        None => transform.translation,
+      },
+    };
+    if let DropStrategy::Fling {
+      friction,
+      max_speed,
+    } = draggable.drop_strategy
+    {
+      self
+        .commands
+        .entity(entity)
+        .remove::<Dragged>()
+        .insert(Flung {
+          velocity: dragged.velocity().clamp_length_max(max_speed),
+          friction,
+        });
+    } else if let Some(animation) = draggable.drop_strategy.animation() {
+      self.commands.entity(entity).insert(DropAnimating {
+        from: transform.translation,
+        to: target,
+        started: self.time.elapsed_seconds_f64(),
+        animation,
+      });
+    } else {
+      transform.translation = target;
+      self.commands.entity(entity).remove::<Dragged>();
+    }
+    self.drag_ended.send(DragEnded { entity });
+  }
+
+  /// Returns the entity to where the drag started without dropping it
+  pub fn cancel(&mut self, entity: Entity, dragged: &Dragged, transform: &mut Transform) {
+    if let Some(ghost) = dragged.ghost {
+      if let Some(ghost) = self.commands.get_entity(ghost) {
+        ghost.despawn_recursive();
+      }
+    }
+    transform.translation = dragged.local_origin;
+    self.commands.entity(entity).remove::<Dragged>();
+    self.drag_cancelled.send(DragCancelled { entity });
+  }
+}
+
+type DraggableQuery<'w, 's> = Query<
+  'w,
+  's,
+  (
+    Entity,
+    &'static Draggable,
+    &'static Transform,
+    &'static GlobalTransform,
+    Option<&'static InteractionPriority>,
+  ),
+  (With<Interactable>, Without<Dragged>, Without<PendingDrag>),
+>;
+
+/// Picks the draggables to drag according to the pick policy.
+/// The position of a draggable in a group is given by `interaction`
+/// when the draggable can be dragged and is under the cursor or touch in that group.
+fn pick_draggables(
+  pick_policy: &PickPolicy,
+  draggables: &DraggableQuery,
+  interaction: impl Fn(Entity, &Draggable, Group) -> Option<Vec2>,
+) -> Vec<(Entity, Group, Vec2)> {
+  let mut candidates = vec![];
+  for (entity, draggable, _, global_transform, priority) in draggables.iter() {
+    let found = draggable
+      .groups
+      .iter()
+      .find_map(|group| Some((*group, interaction(entity, draggable, *group)?)));
+    if let Some((group, position)) = found {
+      let depth = global_transform.translation().z;
+      let priority = priority.copied().unwrap_or_default();
+      candidates.push((entity, group, position, depth, priority));
+    }
+  }
+  candidates.sort_by(|a, b| topmost_first((a.0, a.3, a.4), (b.0, b.3, b.4)));
+
+  match pick_policy {
+    PickPolicy::Topmost => candidates.truncate(1),
+    PickPolicy::All => {}
+    PickPolicy::Custom(filter) => {
+      let entities: Vec<Entity> = candidates.iter().map(|candidate| candidate.0).collect();
+      let picked = filter(&entities);
+      candidates.retain(|candidate| picked.contains(&candidate.0));
+    }
+  }
+  candidates
+    .into_iter()
+    .map(|(entity, group, position, _, _)| (entity, group, position))
+    .collect()
+}
+
+fn start_drag(
+  draggable: &Draggable,
+  transform: &Transform,
+  global_transform: &GlobalTransform,
+  pending: &PendingDrag,
+) -> Dragged {
+  let translation = draggable
+    .hook
+    .unwrap_or(global_transform.translation().truncate() - pending.position);
+  Dragged {
+    group: pending.group,
+    translation,
+    origin: global_transform.translation().truncate(),
+    local_origin: transform.translation,
+    touch_id: pending.touch_id,
+    button: pending.button,
+    virtual_cursor: pending.virtual_cursor,
+    ghost: None,
+    drag_z: draggable
+      .drag_layer
+      .map(|drag_layer| drag_layer.local_z(transform, global_transform)),
+    target: transform.translation,
+    samples: vec![],
+  }
+}
+
+/// Spawns the ghosts that follow the cursor in the ghost drag modes
+#[derive(SystemParam)]
+pub struct DragGhosts<'w, 's> {
+  sprites:       Query<'w, 's, (&'static Sprite, &'static Handle<Image>)>,
+  atlas_sprites: Query<'w, 's, (&'static TextureAtlasSprite, &'static Handle<TextureAtlas>)>,
+}
+
+impl<'w, 's> DragGhosts<'w, 's> {
+  /// Spawns the ghost for the drag mode of the draggable, if it drags a ghost
+  pub fn spawn(
+    &self,
+    commands: &mut Commands,
+    entity: Entity,
+    draggable: &Draggable,
+    global_transform: &GlobalTransform,
+  ) -> Option<Entity> {
+    let transform = global_transform.compute_transform();
+    let ghost = match &draggable.mode {
+      DragMode::Move => return None,
+      DragMode::Ghost { tint } => {
+        if let Ok((sprite, texture)) = self.sprites.get(entity) {
+          commands
+            .spawn(SpriteBundle {
+              sprite: Sprite {
+                color: sprite.color.as_rgba() * Vec4::from(tint.as_rgba()),
+                ..sprite.clone()
+              },
+              texture: texture.clone(),
+              transform,
+              ..Default::default()
+            })
+            .id()
+        } else if let Ok((sprite, texture_atlas)) = self.atlas_sprites.get(entity) {
+          commands
+            .spawn(SpriteSheetBundle {
+              sprite: TextureAtlasSprite {
+                color: sprite.color.as_rgba() * Vec4::from(tint.as_rgba()),
+                ..sprite.clone()
+              },
+              texture_atlas: texture_atlas.clone(),
+              transform,
+              ..Default::default()
+            })
+            .id()
+        } else {
+          warn!(
+            "Draggable {:?} has no sprite to drag a ghost of, moving it instead.",
+            entity
+          );
+          return None;
+        }
+      }
+      DragMode::CustomGhost(spawn) => spawn(commands, entity, transform),
+    };
+    commands.entity(ghost).insert(DragGhost { source: entity });
+    Some(ghost)
+  }
+}
+
+/// Starts drags, dragging every selected draggable along when a selected one is dragged
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct DragStart<'w, 's> {
+  selected: Query<
+    'w,
+    's,
+    (
+      Entity,
+      &'static Draggable,
+      &'static Transform,
+      &'static GlobalTransform,
+    ),
+    (With<Selected>, Without<Dragged>),
+  >,
+  ghosts:       DragGhosts<'w, 's>,
+  drag_started: EventWriter<'w, 's, DragStarted>,
+  commands:     Commands<'w, 's>,
+}
+
+impl<'w, 's> DragStart<'w, 's> {
+  /// Starts dragging right away when the draggable has no threshold or delay,
+  /// otherwise the press is pending until it turns into a drag.
+  /// Returns the entities that started being dragged.
+  pub fn press(
+    &mut self,
+    entity: Entity,
+    draggable: &Draggable,
+    transform: &Transform,
+    global_transform: &GlobalTransform,
+    pending: PendingDrag,
+  ) -> Vec<Entity> {
+    if draggable.threshold <= 0. && draggable.delay <= 0. {
+      self.start(entity, draggable, transform, global_transform, &pending)
+    } else {
+      self.commands.entity(entity).insert(pending);
+      vec![entity]
+    }
+  }
+
+  /// Starts dragging the entity, and every other selected draggable
+  /// at its own offset from the cursor when the entity is selected.
+  /// Returns the entities that started being dragged.
+  pub fn start(
+    &mut self,
+    entity: Entity,
+    draggable: &Draggable,
+    transform: &Transform,
+    global_transform: &GlobalTransform,
+    pending: &PendingDrag,
+  ) -> Vec<Entity> {
+    let mut dragged = start_drag(draggable, transform, global_transform, pending);
+    dragged.ghost = self
+      .ghosts
+      .spawn(&mut self.commands, entity, draggable, global_transform);
+    self
+      .commands
+      .entity(entity)
+      .remove::<PendingDrag>()
+      .remove::<Flung>()
+      .insert(dragged);
+    self.drag_started.send(DragStarted {
+      entity,
+      group: pending.group,
+    });
+
+    let mut started = vec![entity];
+    if self.selected.contains(entity) {
+      for (other, other_draggable, other_transform, other_global_transform) in self.selected.iter()
+      {
+        if other == entity {
+          continue;
+        }
+        let mut dragged = start_drag(
+          other_draggable,
+          other_transform,
+          other_global_transform,
+          pending,
+        );
+        dragged.translation = other_global_transform.translation().truncate() - pending.position;
+        dragged.ghost = self.ghosts.spawn(
+          &mut self.commands,
+          other,
+          other_draggable,
+          other_global_transform,
+        );
+        self
+          .commands
+          .entity(other)
+          .remove::<PendingDrag>()
+          .remove::<Flung>()
+          .insert(dragged);
+        self.drag_started.send(DragStarted {
+          entity: other,
+          group:  pending.group,
+        });
+        started.push(other);
+      }
+    }
+    started
+  }
+}
+
+/// The input pressing draggables with the mouse or a virtual cursor
+#[derive(SystemParam)]
+pub struct MousePress<'w, 's> {
+  time:               Res<'w, Time>,
+  mouse_button_input: Res<'w, Input<MouseButton>>,
+  keyboard_input:     Res<'w, Input<KeyCode>>,
+  virtual_cursors:    VirtualCursors<'w, 's>,
+}
+
+/// Mouse drags start when one of the buttons of a draggable is pressed
+/// while its modifiers are held.
 pub fn mouse_press_start_drag_system(
+  settings: Res<DragSettings>,
   interaction_state: Res<InteractionState>,
-  mouse_button_input: Res<Input<MouseButton>>,
-  draggables: Query<(Entity, &Draggable, &GlobalTransform), With<Interactable>>,
-  mut commands: Commands,
+  input: MousePress,
+  draggables: DraggableQuery,
+  mut drag_start: DragStart,
 ) {
-  if !mouse_button_input.just_pressed(MouseButton::Left) {
-    return;
-  }
-  for (entity, draggable, global_transform) in draggables.iter() {
-    for group in draggable.groups.iter() {
-      if let Some(list) = interaction_state.ordered_interact_list_map.get(group) {
-        if let Some((_, position)) = list.iter().find(|(e, _)| e == &entity) {
-          let translation = draggable
-            .hook
-            .unwrap_or(global_transform.translation().truncate() - *position);
-          commands.entity(entity).insert(Dragged {
-            group: group.clone(),
-            translation,
-            origin: global_transform.translation().truncate(),
-            just_dropped: false,
-            just_dragged: true,
-          });
-          break;
+  // Presses of a virtual cursor count as the left mouse button,
+  // but only in the groups of its interaction source
+  let presses: Vec<(MouseButton, Option<Entity>)> = input
+    .mouse_button_input
+    .get_just_pressed()
+    .map(|button| (*button, None))
+    .chain(
+      input
+        .virtual_cursors
+        .just_pressed()
+        .map(|source| (MouseButton::Left, Some(source))),
+    )
+    .collect();
+
+  let mut started = vec![];
+  for (button, virtual_cursor) in presses {
+    let picked = pick_draggables(
+      &settings.pick_policy,
+      &draggables,
+      |entity, draggable, group| {
+        if started.contains(&entity)
+          || !draggable.buttons.contains(&button)
+          || input
+            .virtual_cursors
+            .get_group(group)
+            .map(|(source, _)| source)
+            != virtual_cursor
+          || !draggable
+            .modifiers
+            .iter()
+            .all(|modifier| modifier.held(&input.keyboard_input))
+        {
+          return None;
         }
+        interaction_state
+          .ordered_interact_list_map
+          .get(&group)?
+          .iter()
+          .find(|(e, _)| *e == entity)
+          .map(|(_, position)| *position)
+      },
+    );
+    for (entity, group, position) in picked {
+      if started.contains(&entity) {
+        continue;
+      }
+      if let Ok((_, draggable, transform, global_transform, _)) = draggables.get(entity) {
+        started.extend(drag_start.press(
+          entity,
+          draggable,
+          transform,
+          global_transform,
+          PendingDrag {
+            group,
+            position,
+            touch_id: None,
+            button: virtual_cursor.is_none().then_some(button),
+            virtual_cursor,
+            started: input.time.elapsed_seconds_f64(),
+          },
+        ));
       }
     }
   }
 }
 
+/// Pending drags turn into drags once the cursor or touch has moved
+/// beyond the threshold and the delay has passed.
+pub fn pending_drag_system(
+  time: Res<Time>,
+  interaction_state: Res<InteractionState>,
+  mouse_button_input: Res<Input<MouseButton>>,
+  touches: Res<Touches>,
+  virtual_cursors: VirtualCursors,
+  pending_drags: Query<
+    (
+      Entity,
+      &Draggable,
+      &PendingDrag,
+      &Transform,
+      &GlobalTransform,
+    ),
+    Without<Dragged>,
+  >,
+  mut drag_start: DragStart,
+) {
+  let mut started = vec![];
+  for (entity, draggable, pending, transform, global_transform) in pending_drags.iter() {
+    // Released presses are dropped by the release systems
+    let released = match pending.touch_id {
+      Some(touch_id) => touches.just_released(touch_id) || touches.just_cancelled(touch_id),
+      None => mouse_released(
+        &mouse_button_input,
+        &virtual_cursors,
+        pending.button,
+        pending.virtual_cursor,
+      ),
+    };
+    if released || started.contains(&entity) {
+      continue;
+    }
+    let position = match interaction_state.position(pending.group, pending.touch_id) {
+      Some(position) => position,
+      None => continue,
+    };
+    let held = time.elapsed_seconds_f64() - pending.started;
+    if position.distance(pending.position) >= draggable.threshold && held >= draggable.delay as f64
+    {
+      started.extend(drag_start.start(entity, draggable, transform, global_transform, pending));
+    }
+  }
+}
+
+/// Whether the mouse button or the virtual cursor that pressed a draggable was released.
+/// Drags of a virtual cursor that has been removed count as released.
+fn mouse_released(
+  mouse_button_input: &Input<MouseButton>,
+  virtual_cursors: &VirtualCursors,
+  button: Option<MouseButton>,
+  virtual_cursor: Option<Entity>,
+) -> bool {
+  match (button, virtual_cursor) {
+    (_, Some(source)) => virtual_cursors
+      .get(source)
+      .is_none_or(|cursor| cursor.just_released()),
+    (Some(button), None) => mouse_button_input.just_released(button),
+    (None, None) => false,
+  }
+}
+
+/// Mouse drags end when the button that started them is released,
+/// pending drags released before turning into drags are dropped.
+#[allow(clippy::type_complexity)]
 pub fn mouse_release_stop_drag_system(
   mouse_button_input: Res<Input<MouseButton>>,
-  mut draggables: Query<(Entity, &Draggable, &mut Dragged, &mut Transform), With<Interactable>>,
-  mut commands: Commands,
+  virtual_cursors: VirtualCursors,
+  mut draggables: Query<
+    (Entity, &Draggable, &Dragged, &mut Transform),
+    (With<Interactable>, Without<DropAnimating>),
+  >,
+  pending_drags: Query<(Entity, &PendingDrag)>,
+  mut drag_stop: DragStop,
 ) {
-  if !mouse_button_input.just_released(MouseButton::Left) {
-    return;
+  for (entity, draggable, dragged, mut transform) in draggables.iter_mut() {
+    if mouse_released(
+      &mouse_button_input,
+      &virtual_cursors,
+      dragged.button,
+      dragged.virtual_cursor,
+    ) {
+      drag_stop.end(entity, draggable, dragged, &mut transform);
+    }
+  }
+  for (entity, pending) in pending_drags.iter() {
+    if mouse_released(
+      &mouse_button_input,
+      &virtual_cursors,
+      pending.button,
+      pending.virtual_cursor,
+    ) {
+      drag_stop.commands.entity(entity).remove::<PendingDrag>();
+    }
   }
-  for (entity, draggable, mut dragged, mut transform) in draggables.iter_mut() {
-    if dragged.just_dropped {
-      if let DropStrategy::Reset = draggable.drop_strategy {
-        transform.translation = dragged.origin.extend(transform.translation.z);
+}
+
+pub fn touch_press_start_drag_system(
+  time: Res<Time>,
+  settings: Res<DragSettings>,
+  interaction_state: Res<InteractionState>,
+  touches: Res<Touches>,
+  draggables: DraggableQuery,
+  mut drag_start: DragStart,
+) {
+  let mut started = vec![];
+  for touch in touches.iter_just_pressed() {
+    let picked = pick_draggables(&settings.pick_policy, &draggables, |entity, _, group| {
+      if started.contains(&entity) {
+        return None;
       }
-      commands.entity(entity).remove::<Dragged>();
-    } else {
-      dragged.just_dropped = true;
+      interaction_state
+        .touch_interact_list_map
+        .get(&group)?
+        .get(&touch.id())?
+        .iter()
+        .find(|(e, _)| *e == entity)
+        .map(|(_, position)| *position)
+    });
+    for (entity, group, position) in picked {
+      if started.contains(&entity) {
+        continue;
+      }
+      if let Ok((_, draggable, transform, global_transform, _)) = draggables.get(entity) {
+        started.extend(drag_start.press(
+          entity,
+          draggable,
+          transform,
+          global_transform,
+          PendingDrag {
+            group,
+            position,
+            touch_id: Some(touch.id()),
+            button: None,
+            virtual_cursor: None,
+            started: time.elapsed_seconds_f64(),
+          },
+        ));
+      }
+    }
+  }
+}
+
+/// The events cancelling drags
+#[derive(SystemParam)]
+pub struct CancelEvents<'w, 's> {
+  window_focused: EventReader<'w, 's, WindowFocused>,
+  cursor_left:    EventReader<'w, 's, CursorLeft>,
+  cancel_drag:    EventReader<'w, 's, CancelDrag>,
+}
+
+/// Cancels drags on the cancel keys, on focus loss, when the cursor leaves a window
+/// and on `CancelDrag` events. Cancelled entities always return to where the drag
+/// started, whatever their drop strategy, and cancelled pending drags are dropped.
+/// Mouse drags in the groups of an edge scrolling source are kept when the cursor
+/// leaves a window, since pushing the cursor past the edge is how they scroll.
+pub fn cancel_drag_system(
+  settings: Res<DragSettings>,
+  keyboard_input: Res<Input<KeyCode>>,
+  mut events: CancelEvents,
+  edge_scroll_sources: Query<&InteractionSource, With<EdgeScroll>>,
+  mut draggables: Query<(Entity, &Dragged, &mut Transform), Without<DropAnimating>>,
+  pending_drags: Query<(Entity, &PendingDrag)>,
+  mut drag_stop: DragStop,
+) {
+  let mut cancel_all = keyboard_input.any_just_pressed(settings.cancel_keys.iter().copied());
+  // Every event is read, so none are left over for the next frame
+  let focus_lost = events
+    .window_focused
+    .iter()
+    .fold(false, |lost, event| lost | !event.focused);
+  if focus_lost {
+    cancel_all |= settings.cancel_on_focus_loss;
+  }
+  let cursor_left = events.cursor_left.iter().count() > 0 && settings.cancel_on_cursor_left;
+  let cancel_mouse = |group: Group| {
+    cursor_left
+      && !edge_scroll_sources
+        .iter()
+        .any(|source| source.groups.contains(&group))
+  };
+  let mut cancel_entities = vec![];
+  for event in events.cancel_drag.iter() {
+    match event.entity {
+      Some(entity) => cancel_entities.push(entity),
+      None => cancel_all = true,
+    }
+  }
+
+  for (entity, dragged, mut transform) in draggables.iter_mut() {
+    if cancel_all
+      || (dragged.button.is_some() && cancel_mouse(dragged.group))
+      || cancel_entities.contains(&entity)
+    {
+      drag_stop.cancel(entity, dragged, &mut transform);
+    }
+  }
+  for (entity, pending) in pending_drags.iter() {
+    if cancel_all
+      || (pending.button.is_some() && cancel_mouse(pending.group))
+      || cancel_entities.contains(&entity)
+    {
+      drag_stop.commands.entity(entity).remove::<PendingDrag>();
+    }
+  }
+}
+
+/// Touch drags end when the touch is released and are cancelled
+/// when the touch is cancelled.
+#[allow(clippy::type_complexity)]
+pub fn touch_release_stop_drag_system(
+  touches: Res<Touches>,
+  mut draggables: Query<
+    (Entity, &Draggable, &Dragged, &mut Transform),
+    (With<Interactable>, Without<DropAnimating>),
+  >,
+  pending_drags: Query<(Entity, &PendingDrag)>,
+  mut drag_stop: DragStop,
+) {
+  for (entity, pending) in pending_drags.iter() {
+    if let Some(touch_id) = pending.touch_id {
+      if touches.just_released(touch_id) || touches.just_cancelled(touch_id) {
+        drag_stop.commands.entity(entity).remove::<PendingDrag>();
+      }
+    }
+  }
+  for (entity, draggable, dragged, mut transform) in draggables.iter_mut() {
+    let touch_id = match dragged.touch_id {
+      Some(touch_id) => touch_id,
+      None => continue,
+    };
+    if touches.just_released(touch_id) {
+      drag_stop.end(entity, draggable, dragged, &mut transform);
+    } else if touches.just_cancelled(touch_id) {
+      drag_stop.cancel(entity, dragged, &mut transform);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use bevy::ecs::event::ManualEventReader;
+
+  use super::*;
+
+  fn end_drags(
+    mut draggables: Query<(Entity, &Draggable, &Dragged, &mut Transform)>,
+    mut drag_stop: DragStop,
+  ) {
+    for (entity, draggable, dragged, mut transform) in draggables.iter_mut() {
+      drag_stop.end(entity, draggable, dragged, &mut transform);
+    }
+  }
+
+  #[test]
+  fn reset_returns_parented_draggable_to_its_origin() {
+    let mut app = App::new();
+    app
+      .add_plugin(HierarchyPlugin)
+      .add_plugin(TransformPlugin)
+      .init_resource::<Time>()
+      .init_resource::<InteractionState>()
+      .add_event::<DragEnded>()
+      .add_event::<DragCancelled>()
+      .add_event::<Dropped>()
+      .add_event::<DropRejected>()
+      .add_system(end_drags);
+
+    let root = app
+      .world
+      .spawn(TransformBundle::from_transform(Transform {
+        translation: Vec3::new(10., -5., 0.),
+        rotation:    Quat::from_rotation_z(0.7),
+        scale:       Vec3::new(3., 2., 1.),
+      }))
+      .id();
+    let middle = app
+      .world
+      .spawn(TransformBundle::from_transform(Transform {
+        translation: Vec3::new(4., 7., 1.),
+        rotation:    Quat::from_rotation_z(-0.3),
+        scale:       Vec3::new(0.5, 0.5, 1.),
+      }))
+      .id();
+    let leaf = app
+      .world
+      .spawn((
+        TransformBundle::from_transform(Transform::from_xyz(6., -2., 0.5)),
+        Interactable::default(),
+        Draggable {
+          drop_strategy: DropStrategy::Reset,
+          ..Default::default()
+        },
+      ))
+      .id();
+    app.world.entity_mut(root).push_children(&[middle]);
+    app.world.entity_mut(middle).push_children(&[leaf]);
+    app.update();
+
+    let start_transform = *app.world.get::<Transform>(leaf).unwrap();
+    let start_global = *app.world.get::<GlobalTransform>(leaf).unwrap();
+    assert_ne!(start_global.translation(), start_transform.translation);
+
+    let dragged = start_drag(
+      app.world.get::<Draggable>(leaf).unwrap(),
+      &start_transform,
+      &start_global,
+      &PendingDrag {
+        group:          Group::default(),
+        position:       start_global.translation().truncate(),
+        touch_id:       None,
+        button:         Some(MouseButton::Left),
+        virtual_cursor: None,
+        started:        0.,
+      },
+    );
+    app.world.entity_mut(leaf).insert(dragged);
+    app.world.get_mut::<Transform>(leaf).unwrap().translation += Vec3::new(13., 21., 0.);
+    app.update();
+
+    assert!(app.world.get::<Dragged>(leaf).is_none());
+    assert_eq!(
+      app.world.get::<Transform>(leaf).unwrap().translation,
+      start_transform.translation
+    );
+    assert!(app
+      .world
+      .get::<GlobalTransform>(leaf)
+      .unwrap()
+      .translation()
+      .abs_diff_eq(start_global.translation(), 1e-5));
+  }
+
+  #[test]
+  fn group_drag_from_pending_press_starts_every_selected_draggable_once() {
+    let mut app = App::new();
+    app
+      .init_resource::<Time>()
+      .init_resource::<InteractionState>()
+      .init_resource::<Input<MouseButton>>()
+      .init_resource::<Touches>()
+      .add_event::<DragStarted>()
+      .add_system(pending_drag_system);
+
+    let group = Group::default();
+    let pending = || PendingDrag {
+      group,
+      position: Vec2::ZERO,
+      touch_id: None,
+      button: Some(MouseButton::Left),
+      virtual_cursor: None,
+      started: 0.,
+    };
+    let mut spawn_selected = |x: f32| {
+      app
+        .world
+        .spawn((
+          SpriteBundle::default(),
+          Interactable::default(),
+          Draggable {
+            mode: DragMode::Ghost { tint: Color::WHITE },
+            ..Default::default()
+          },
+          Selected,
+        ))
+        .insert(Transform::from_xyz(x, 0., 0.))
+        .insert(GlobalTransform::from_xyz(x, 0., 0.))
+        .id()
+    };
+    let draggables = [spawn_selected(0.), spawn_selected(0.), spawn_selected(40.)];
+    // The overlapping draggables were both pressed, as with `PickPolicy::All`
+    for entity in &draggables[..2] {
+      app.world.entity_mut(*entity).insert(pending());
+    }
+    app
+      .world
+      .resource_mut::<Input<MouseButton>>()
+      .press(MouseButton::Left);
+    app
+      .world
+      .resource_mut::<InteractionState>()
+      .cursor_positions
+      .insert(group, Vec2::new(10., 0.));
+
+    let mut reader = ManualEventReader::<DragStarted>::default();
+    let mut drag_started = 0;
+    for _ in 0..3 {
+      app.update();
+      drag_started += reader
+        .iter(app.world.resource::<Events<DragStarted>>())
+        .count();
+    }
+
+    assert_eq!(drag_started, draggables.len());
+    for entity in draggables {
+      assert!(app.world.get::<Dragged>(entity).is_some());
+      assert!(app.world.get::<PendingDrag>(entity).is_none());
     }
+    let ghosts = app
+      .world
+      .query::<&DragGhost>()
+      .iter(&app.world)
+      .count();
+    assert_eq!(ghosts, draggables.len());
   }
 }
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use super::{Group, InteractionState};
+
+/// Sent when the cursor starts hovering over an interactable entity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverStarted {
+  pub entity: Entity,
+  pub group:  Group,
+}
+
+/// Sent when the cursor stops hovering over an interactable entity.
+/// The entity may already have been despawned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HoverEnded {
+  pub entity: Entity,
+  pub group:  Group,
+}
+
+/// Sent every frame for every entity the cursor is hovering over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hovering {
+  pub entity:   Entity,
+  pub group:    Group,
+  pub position: Vec2,
+}
+
+/// This system compares the interactions of this frame with those
+/// of the previous frame and sends the matching hover events.
+pub fn hover_event_system(
+  interaction_state: Res<InteractionState>,
+  mut previous: Local<HashMap<Group, Vec<(Entity, Vec2)>>>,
+  mut hover_started: EventWriter<HoverStarted>,
+  mut hover_ended: EventWriter<HoverEnded>,
+  mut hovering: EventWriter<Hovering>,
+) {
+  let current = &interaction_state.ordered_interact_list_map;
+
+  for (group, list) in previous.iter() {
+    for (entity, _) in list.iter() {
+      if !contains(current, group, entity) {
+        hover_ended.send(HoverEnded {
+          entity: *entity,
+          group:  *group,
+        });
+      }
+    }
+  }
+
+  for (group, list) in current.iter() {
+    for (entity, position) in list.iter() {
+      if !contains(&previous, group, entity) {
+        hover_started.send(HoverStarted {
+          entity: *entity,
+          group:  *group,
+        });
+      }
+      hovering.send(Hovering {
+        entity:   *entity,
+        group:    *group,
+        position: *position,
+      });
+    }
+  }
+
+  *previous = current.clone();
+}
+
+fn contains(map: &HashMap<Group, Vec<(Entity, Vec2)>>, group: &Group, entity: &Entity) -> bool {
+  map
+    .get(group)
+    .is_some_and(|list| list.iter().any(|(e, _)| e == entity))
+}
@@ -1,16 +1,35 @@
-use std::collections::HashMap;
+use std::{
+  cmp::{Ordering, Reverse},
+  collections::{HashMap, HashSet},
+  fmt,
+};
 
 use bevy::prelude::*;
 
 use bevy::{
-  ecs::event::{Events, ManualEventReader},
-  render::camera::Camera,
+  ecs::{
+    event::{Events, ManualEventReader},
+    system::SystemParam,
+  },
+  input::InputSystem,
+  math::Affine3A,
+  render::camera::{Camera, RenderTarget},
+  transform::TransformSystem,
   window::WindowId,
 };
 #[cfg(feature = "debug")]
 use bevy_prototype_lyon::plugin::ShapePlugin;
 
+pub mod click;
 pub mod drag;
+pub mod hover;
+pub mod select;
+pub mod shape;
+pub mod virtual_cursor;
+
+use hover::{hover_event_system, HoverEnded, HoverStarted, Hovering};
+use shape::Shape;
+use virtual_cursor::{virtual_cursor_system, VirtualCursor, VirtualCursorSystem};
 
 /// The interaction plugin adds cursor interactions for entities
 /// with the Interactable component.
@@ -20,13 +39,39 @@ impl Plugin for InteractionPlugin {
   fn build(&self, app: &mut App) {
     app
       .init_resource::<InteractionState>()
-      .add_system_to_stage(CoreStage::PostUpdate, interaction_state_system)
-      .add_system_to_stage(CoreStage::PostUpdate, interaction_system);
+      .init_resource::<InteractionSettings>()
+      .add_event::<InteractionWarning>()
+      .add_event::<HoverStarted>()
+      .add_event::<HoverEnded>()
+      .add_event::<Hovering>()
+      // Virtual cursors cost nothing without VirtualCursor components,
+      // so they are always moved and other plugins can be ordered after them.
+      .add_system_to_stage(
+        CoreStage::PreUpdate,
+        virtual_cursor_system
+          .label(VirtualCursorSystem)
+          .after(InputSystem),
+      )
+      // Cursor positions are projected through the camera transforms of this frame
+      .add_system_to_stage(
+        CoreStage::PostUpdate,
+        interaction_state_system.after(TransformSystem::TransformPropagate),
+      )
+      .add_system_to_stage(
+        CoreStage::PostUpdate,
+        interaction_system
+          .after(TransformSystem::TransformPropagate)
+          .after(interaction_state_system),
+      )
+      .add_system_to_stage(
+        CoreStage::PostUpdate,
+        hover_event_system.after(interaction_system),
+      );
   }
 }
 
 /// The interaction debug plugin is a drop-in replacement for the interaction
-/// plugin that will draw the bounding boxes for Interactable components.
+/// plugin that will draw the hit shapes for Interactable components.
 /// This debug plugin requires the bevy_prototype_lyon ShapePlugin
 /// in order to function.
 #[cfg(feature = "debug")]
@@ -54,10 +99,15 @@ pub struct Group(pub u8);
 
 #[derive(Default, Resource)]
 pub struct InteractionState {
+  /// The entities under the cursor for each group, topmost first
   pub ordered_interact_list_map: HashMap<Group, Vec<(Entity, Vec2)>>,
   pub cursor_positions:          HashMap<Group, Vec2>,
-  pub last_window_id:            WindowId,
-  pub last_cursor_position:      Vec2,
+  /// The cursor position in window coordinates for every window the cursor is in
+  pub window_cursor_positions:   HashMap<WindowId, Vec2>,
+  /// The interaction point of every active touch for each group, by touch id
+  pub touch_positions:           HashMap<Group, HashMap<u64, Vec2>>,
+  /// The entities under every active touch for each group, topmost first
+  pub touch_interact_list_map:   HashMap<Group, HashMap<u64, Vec<(Entity, Vec2)>>>,
 }
 
 impl InteractionState {
@@ -67,9 +117,44 @@ impl InteractionState {
       None => vec![],
     }
   }
+
+  /// The topmost entity under the cursor for the given group
+  pub fn topmost(&self, group: Group) -> Option<(Entity, Vec2)> {
+    self
+      .ordered_interact_list_map
+      .get(&group)
+      .and_then(|interactions| interactions.first().copied())
+  }
+
+  /// The entities under the given touch for the given group, topmost first
+  pub fn get_touch(&self, group: Group, touch_id: u64) -> Vec<(Entity, Vec2)> {
+    self
+      .touch_interact_list_map
+      .get(&group)
+      .and_then(|touches| touches.get(&touch_id))
+      .cloned()
+      .unwrap_or_default()
+  }
+
+  /// The interaction point for the given group of the given touch,
+  /// or of the cursor when no touch is given.
+  pub fn position(&self, group: Group, touch_id: Option<u64>) -> Option<Vec2> {
+    match touch_id {
+      Some(touch_id) => self
+        .touch_positions
+        .get(&group)
+        .and_then(|touches| touches.get(&touch_id))
+        .copied(),
+      None => self.cursor_positions.get(&group).copied(),
+    }
+  }
 }
 
-/// Attach an interaction source to cameras you want to interact from
+/// Attach an interaction source to cameras you want to interact from.
+/// Only the cursor in the window the camera renders to is used,
+/// and only while it is within the viewport of the camera.
+/// Touches are used when the camera renders to the primary window.
+/// When the camera also has a VirtualCursor it is used instead of the mouse cursor.
 #[derive(Component)]
 pub struct InteractionSource {
   pub groups:        Vec<Group>,
@@ -85,101 +170,333 @@ impl Default for InteractionSource {
   }
 }
 
+/// Configures how the interaction plugin deals with misconfiguration
+#[derive(Default, Resource)]
+pub struct InteractionSettings {
+  /// Panic on any interaction warning instead of logging it,
+  /// useful to catch misconfiguration in tests.
+  pub strict: bool,
+}
+
+/// Sent whenever the interaction sources are misconfigured.
+/// The same warning is sent every frame for as long as the problem persists,
+/// but it is only logged the first time.
+#[derive(Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum InteractionWarning {
+  /// The interaction source has no camera and is ignored
+  MissingCamera { source: Entity },
+  /// Multiple interaction sources that share a group have the cursor in their viewport.
+  /// The source with the highest camera priority is used for the group,
+  /// when priorities are equal the source with the lowest entity id is used.
+  GroupConflict {
+    group:   Group,
+    used:    Entity,
+    ignored: Entity,
+  },
+}
+
+impl fmt::Display for InteractionWarning {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InteractionWarning::MissingCamera { source } => write!(
+        f,
+        "Interaction source {:?} has no camera and is ignored.",
+        source
+      ),
+      InteractionWarning::GroupConflict {
+        group,
+        used,
+        ignored,
+      } => write!(
+        f,
+        "Multiple interaction sources have been added to interaction group {:?}, \
+         using {:?} and ignoring {:?}.",
+        group, used, ignored
+      ),
+    }
+  }
+}
+
+/// The cursor and touch input the interaction points are calculated from
+#[derive(SystemParam)]
+struct PointerInput<'w, 's> {
+  cursor_moved: Res<'w, Events<CursorMoved>>,
+  cursor_left:  EventReader<'w, 's, CursorLeft>,
+  touches:      Res<'w, Touches>,
+  windows:      Res<'w, Windows>,
+}
+
 /// This system calculates the interaction point for each group
-/// whenever the cursor is moved.
+/// from the cursor and from every active touch.
+#[allow(clippy::type_complexity)]
 fn interaction_state_system(
   mut interaction_state: ResMut<InteractionState>,
-  cursor_moved: Res<Events<CursorMoved>>,
-  windows: Res<Windows>,
-  mut sources: Query<(&mut InteractionSource, &GlobalTransform, Option<&Camera>)>,
+  settings: Res<InteractionSettings>,
+  mut input: PointerInput,
+  mut sources: Query<(
+    Entity,
+    &mut InteractionSource,
+    &GlobalTransform,
+    Option<&Camera>,
+    Option<&VirtualCursor>,
+  )>,
+  mut warnings: EventWriter<InteractionWarning>,
+  mut reported: Local<HashSet<InteractionWarning>>,
 ) {
   interaction_state.cursor_positions.clear();
+  interaction_state.touch_positions.clear();
+
+  let mut report = |warning: InteractionWarning| {
+    if settings.strict {
+      panic!("{}", warning);
+    }
+    if reported.insert(warning) {
+      warn!("{}", warning);
+    }
+    warnings.send(warning);
+  };
+
+  let mut sources: Vec<_> = sources.iter_mut().collect();
+  sources.sort_by_key(|(entity, _, _, camera, _)| {
+    (
+      Reverse(camera.map_or(isize::MIN, |camera| camera.priority)),
+      *entity,
+    )
+  });
 
-  for (mut interact_source, global_transform, camera) in sources.iter_mut() {
-    if let Some(evt) = interact_source.cursor_events.iter(&cursor_moved).last() {
-      interaction_state.last_window_id = evt.id;
-      interaction_state.last_cursor_position = evt.position;
+  for (_, interact_source, _, _, _) in sources.iter_mut() {
+    for evt in interact_source.cursor_events.iter(&input.cursor_moved) {
+      interaction_state
+        .window_cursor_positions
+        .insert(evt.id, evt.position);
     }
-    let projection_matrix = match camera {
-      Some(camera) => camera.projection_matrix(),
-      None => panic!("Interacting without camera not supported."),
+  }
+  for evt in input.cursor_left.iter() {
+    interaction_state.window_cursor_positions.remove(&evt.id);
+  }
+
+  let mut group_sources = HashMap::new();
+
+  for (entity, interact_source, global_transform, camera, virtual_cursor) in sources {
+    let camera = match camera {
+      Some(camera) => camera,
+      None => {
+        report(InteractionWarning::MissingCamera { source: entity });
+        continue;
+      }
+    };
+    // Cameras rendering to an image have no cursor to interact with
+    let window_id = match camera.target {
+      RenderTarget::Window(window_id) => window_id,
+      RenderTarget::Image(_) => continue,
+    };
+    let window = match input.windows.get(window_id) {
+      Some(window) => window,
+      None => continue,
     };
-    if let Some(window) = windows.get(interaction_state.last_window_id) {
-      let screen_size = Vec2::from([window.width() as f32, window.height() as f32]);
-      let cursor_position = interaction_state.last_cursor_position;
-      let cursor_position_ndc = (cursor_position / screen_size) * 2.0 - Vec2::from([1.0, 1.0]);
-      let camera_matrix = global_transform.compute_matrix();
-      let ndc_to_world: Mat4 = camera_matrix * projection_matrix.inverse();
-      let cursor_position = ndc_to_world
-        .transform_point3(cursor_position_ndc.extend(1.0))
-        .truncate();
 
+    let cursor_position = match virtual_cursor {
+      Some(virtual_cursor) => Some(virtual_cursor.position),
+      None => interaction_state
+        .window_cursor_positions
+        .get(&window_id)
+        .copied(),
+    }
+    .and_then(|position| window_to_world(camera, global_transform, window, position));
+    if let Some(cursor_position) = cursor_position {
       for group in &interact_source.groups {
-        if interaction_state
-          .cursor_positions
-          .insert(*group, cursor_position)
-          .is_some()
-        {
-          panic!(
-            "Multiple interaction sources have been added to interaction group {:?}",
-            group
-          );
+        match group_sources.get(group) {
+          Some(used) => report(InteractionWarning::GroupConflict {
+            group:   *group,
+            used:    *used,
+            ignored: entity,
+          }),
+          None => {
+            group_sources.insert(*group, entity);
+            interaction_state
+              .cursor_positions
+              .insert(*group, cursor_position);
+          }
+        }
+      }
+    }
+
+    if window_id != WindowId::primary() {
+      continue;
+    }
+    for touch in input.touches.iter() {
+      // Touch positions are measured from the top left of the window
+      let position = Vec2::new(touch.position().x, window.height() - touch.position().y);
+      if let Some(touch_position) = window_to_world(camera, global_transform, window, position) {
+        for group in &interact_source.groups {
+          interaction_state
+            .touch_positions
+            .entry(*group)
+            .or_default()
+            .entry(touch.id())
+            .or_insert(touch_position);
         }
       }
     }
   }
 }
 
+/// Projects a position in window coordinates, measured from the bottom left,
+/// through the viewport of the camera into the world.
+/// Positions outside of the viewport have no world position.
+fn window_to_world(
+  camera: &Camera,
+  camera_transform: &GlobalTransform,
+  window: &Window,
+  position: Vec2,
+) -> Option<Vec2> {
+  let screen_size = Vec2::from([window.width(), window.height()]);
+  // The viewport is measured from the top left of the window
+  let (viewport_min, viewport_max) = camera
+    .logical_viewport_rect()
+    .unwrap_or((Vec2::ZERO, screen_size));
+  let position_from_top = Vec2::new(position.x, screen_size.y - position.y);
+  if !(viewport_min.x..viewport_max.x).contains(&position_from_top.x)
+    || !(viewport_min.y..viewport_max.y).contains(&position_from_top.y)
+  {
+    return None;
+  }
+  let viewport_position = Vec2::new(
+    position_from_top.x - viewport_min.x,
+    viewport_max.y - position_from_top.y,
+  );
+  let viewport_size = viewport_max - viewport_min;
+  let position_ndc = (viewport_position / viewport_size) * 2.0 - Vec2::from([1.0, 1.0]);
+  let camera_matrix = camera_transform.compute_matrix();
+  let ndc_to_world: Mat4 = camera_matrix * camera.projection_matrix().inverse();
+  Some(
+    ndc_to_world
+      .transform_point3(position_ndc.extend(1.0))
+      .truncate(),
+  )
+}
+
 /// This component makes an entity interactable with the mouse cursor
 #[derive(Component)]
 pub struct Interactable {
   /// The interaction groups this interactable entity belongs to
-  pub groups:       Vec<Group>,
+  pub groups: Vec<Group>,
   /// The interaction area for the interactable entity
-  pub bounding_box: (Vec2, Vec2),
+  pub shape:  Shape,
 }
 
 impl Default for Interactable {
   fn default() -> Self {
     Self {
-      groups:       vec![Group::default()],
-      bounding_box: (Vec2::default(), Vec2::default()),
+      groups: vec![Group::default()],
+      shape:  Shape::default(),
     }
   }
 }
 
+/// Breaks ties between overlapping interactables at the same depth,
+/// the entity with the highest priority is considered to be on top.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InteractionPriority(pub i32);
+
+struct HitTarget<'a> {
+  entity:         Entity,
+  world_to_local: Affine3A,
+  depth:          f32,
+  priority:       InteractionPriority,
+  interactable:   &'a Interactable,
+}
+
 /// This system checks what for what groups an entity is currently interacted with
-fn interaction_system(
+pub(crate) fn interaction_system(
   mut interaction_state: ResMut<InteractionState>,
-  interactables: Query<(Entity, &GlobalTransform, &Interactable)>,
+  interactables: Query<(
+    Entity,
+    &GlobalTransform,
+    &Interactable,
+    Option<&InteractionPriority>,
+  )>,
 ) {
-  interaction_state.ordered_interact_list_map.clear();
+  let targets: Vec<_> = interactables
+    .iter()
+    .map(
+      |(entity, global_transform, interactable, priority)| HitTarget {
+        entity,
+        world_to_local: global_transform.affine().inverse(),
+        depth: global_transform.translation().z,
+        priority: priority.copied().unwrap_or_default(),
+        interactable,
+      },
+    )
+    .collect();
 
-  for (entity, global_transform, interactable) in interactables.iter() {
-    let cursor_positions = interaction_state.cursor_positions.clone();
-    for (group, cursor_position) in cursor_positions {
-      if !interactable.groups.contains(&group) {
-        continue;
-      }
-      // TODO: use bounding_mesh
-      let relative_cursor_position = (cursor_position - global_transform.translation().truncate())
-        / Transform::from(*global_transform).scale.truncate();
-      if (interactable.bounding_box.0.x..interactable.bounding_box.1.x)
-        .contains(&relative_cursor_position.x)
-        && (interactable.bounding_box.0.y..interactable.bounding_box.1.y)
-          .contains(&relative_cursor_position.y)
-      {
-        let interaction = (entity, cursor_position);
-        if let Some(list) = interaction_state.ordered_interact_list_map.get_mut(&group) {
-          list.push(interaction)
-        } else {
-          interaction_state
-            .ordered_interact_list_map
-            .insert(group, vec![interaction]);
-        }
+  let mut ordered_interact_list_map = HashMap::new();
+  for (group, cursor_position) in interaction_state.cursor_positions.iter() {
+    let interactions = ordered_interactions(&targets, *group, *cursor_position);
+    if !interactions.is_empty() {
+      ordered_interact_list_map.insert(*group, interactions);
+    }
+  }
+
+  let mut touch_interact_list_map: HashMap<Group, HashMap<u64, _>> = HashMap::new();
+  for (group, touches) in interaction_state.touch_positions.iter() {
+    for (touch_id, touch_position) in touches.iter() {
+      let interactions = ordered_interactions(&targets, *group, *touch_position);
+      if !interactions.is_empty() {
+        touch_interact_list_map
+          .entry(*group)
+          .or_default()
+          .insert(*touch_id, interactions);
       }
     }
   }
+
+  interaction_state.ordered_interact_list_map = ordered_interact_list_map;
+  interaction_state.touch_interact_list_map = touch_interact_list_map;
+}
+
+/// Orders interactables by their entity, depth and priority, topmost first.
+/// The highest depth is on top, ties are broken by the highest `InteractionPriority`
+/// and then by the entity, so the order is stable.
+pub(crate) fn topmost_first(
+  (a_entity, a_depth, a_priority): (Entity, f32, InteractionPriority),
+  (b_entity, b_depth, b_priority): (Entity, f32, InteractionPriority),
+) -> Ordering {
+  b_depth
+    .total_cmp(&a_depth)
+    .then(b_priority.cmp(&a_priority))
+    .then(a_entity.cmp(&b_entity))
+}
+
+/// The targets in the group that contain the position, topmost first
+fn ordered_interactions(
+  targets: &[HitTarget],
+  group: Group,
+  position: Vec2,
+) -> Vec<(Entity, Vec2)> {
+  let mut hits: Vec<_> = targets
+    .iter()
+    .filter(|target| {
+      // The position is placed on the plane of the entity so rotation,
+      // non-uniform scale and shear from parents are all undone.
+      let local_position = target
+        .world_to_local
+        .transform_point3(position.extend(target.depth))
+        .truncate();
+      target.interactable.groups.contains(&group)
+        && target.interactable.shape.contains(local_position)
+    })
+    .collect();
+  hits.sort_by(|a, b| {
+    topmost_first(
+      (a.entity, a.depth, a.priority),
+      (b.entity, b.depth, b.priority),
+    )
+  });
+  hits
+    .iter()
+    .map(|target| (target.entity, position))
+    .collect()
 }
 
 #[cfg(feature = "debug")]
@@ -208,27 +525,31 @@ fn setup_interaction_debug(
       false => 255,
     };
 
-    let bounding_mesh = shapes::Polygon {
-      points: vec![
-        Vec2::new(interactable.bounding_box.0.x, interactable.bounding_box.0.y),
-        Vec2::new(interactable.bounding_box.1.x, interactable.bounding_box.0.y),
-        Vec2::new(interactable.bounding_box.1.x, interactable.bounding_box.1.y),
-        Vec2::new(interactable.bounding_box.0.x, interactable.bounding_box.1.y),
-      ],
-      closed: true,
+    let draw_mode = DrawMode::Stroke(StrokeMode::new(Color::rgb_u8(red, green, blue), 1.0));
+    let bundle = match &interactable.shape {
+      Shape::Circle { center, radius } => GeometryBuilder::build_as(
+        &shapes::Circle {
+          center: *center,
+          radius: *radius,
+        },
+        draw_mode,
+        Transform::default(),
+      ),
+      shape => GeometryBuilder::build_as(
+        &shapes::Polygon {
+          points: shape.outline(32),
+          closed: true,
+        },
+        draw_mode,
+        Transform::default(),
+      ),
     };
 
-    let child = commands
-      .spawn_bundle(GeometryBuilder::build_as(
-        &bounding_mesh,
-        DrawMode::Stroke(StrokeMode::new(Color::rgb_u8(red, green, blue), 1.0)),
-        Transform::default(),
-      ))
-      .id();
+    let child = commands.spawn(bundle).id();
 
     commands
       .entity(entity)
-      .push_children(&vec![child])
+      .push_children(&[child])
       .insert(DebugInteractable { child });
   }
 }
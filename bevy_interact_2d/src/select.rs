@@ -0,0 +1,167 @@
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*};
+
+use super::{
+  drag::Modifier,
+  virtual_cursor::{VirtualCursorSystem, VirtualCursors},
+  Group, InteractionState,
+};
+
+/// Marks an interactable entity that can be selected
+#[derive(Component, Default)]
+pub struct Selectable;
+
+/// Marks a selected entity.
+/// Dragging a selected draggable drags every selected draggable.
+#[derive(Component)]
+pub struct Selected;
+
+/// A rubber band selection in world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marquee {
+  pub start: Vec2,
+  pub end:   Vec2,
+}
+
+impl Marquee {
+  pub fn rect(&self) -> Rect {
+    Rect::from_corners(self.start, self.end)
+  }
+}
+
+/// Configures how entities are selected
+#[derive(Resource)]
+pub struct SelectionSettings {
+  /// The group whose cursor selects entities
+  pub group:            Group,
+  pub button:           MouseButton,
+  /// Holding any of these toggles entities in and out of the selection
+  /// instead of replacing it.
+  pub toggle_modifiers: Vec<Modifier>,
+}
+
+impl Default for SelectionSettings {
+  fn default() -> Self {
+    Self {
+      group:            Group::default(),
+      button:           MouseButton::Left,
+      toggle_modifiers: vec![Modifier::Shift, Modifier::Control],
+    }
+  }
+}
+
+/// The marquee being dragged out, if any
+#[derive(Default, Resource)]
+pub struct SelectionState {
+  pub marquee: Option<Marquee>,
+}
+
+/// The selection plugin selects selectable entities by clicking them
+/// or by dragging a marquee around them.
+pub struct SelectionPlugin;
+impl Plugin for SelectionPlugin {
+  fn build(&self, app: &mut App) {
+    app
+      .init_resource::<InteractionState>()
+      .init_resource::<SelectionSettings>()
+      .init_resource::<SelectionState>()
+      // Selecting runs before the update stage, so drags started by the same press
+      // already see the new selection.
+      .add_system_to_stage(
+        CoreStage::PreUpdate,
+        selection_system
+          .after(InputSystem)
+          .after(VirtualCursorSystem),
+      );
+  }
+}
+
+/// The buttons, keys and virtual cursors selecting entities
+#[derive(SystemParam)]
+pub struct SelectionInput<'w, 's> {
+  mouse_button_input: Res<'w, Input<MouseButton>>,
+  keyboard_input:     Res<'w, Input<KeyCode>>,
+  virtual_cursors:    VirtualCursors<'w, 's>,
+}
+
+/// Pressing a selectable entity selects it, pressing empty space starts a marquee
+/// that selects the entities whose position it covers when released.
+/// A marquee released outside of the window ends where the cursor was last seen.
+pub fn selection_system(
+  settings: Res<SelectionSettings>,
+  interaction_state: Res<InteractionState>,
+  input: SelectionInput,
+  mut selection_state: ResMut<SelectionState>,
+  selectables: Query<(Entity, &GlobalTransform, Option<&Selected>), With<Selectable>>,
+  mut commands: Commands,
+) {
+  let position = interaction_state.position(settings.group, None);
+  let toggle = settings
+    .toggle_modifiers
+    .iter()
+    .any(|modifier| modifier.held(&input.keyboard_input));
+  // A virtual cursor of the group presses in place of the button
+  let (pressed, released) = match input.virtual_cursors.get_group(settings.group) {
+    Some((_, cursor)) => (cursor.just_pressed(), cursor.just_released()),
+    None => (
+      input.mouse_button_input.just_pressed(settings.button),
+      input.mouse_button_input.just_released(settings.button),
+    ),
+  };
+
+  if let (true, Some(position)) = (pressed, position) {
+    let hit = interaction_state
+      .get_group(settings.group)
+      .into_iter()
+      .find_map(|(entity, _)| selectables.get(entity).ok());
+    match hit {
+      Some((entity, _, selected)) if toggle => {
+        if selected.is_some() {
+          commands.entity(entity).remove::<Selected>();
+        } else {
+          commands.entity(entity).insert(Selected);
+        }
+      }
+      // Pressing a selected entity keeps the selection so it can be dragged together
+      Some((_, _, Some(_))) => {}
+      Some((entity, _, None)) => {
+        for (other, _, selected) in selectables.iter() {
+          if selected.is_some() {
+            commands.entity(other).remove::<Selected>();
+          }
+        }
+        commands.entity(entity).insert(Selected);
+      }
+      // Pressing other interactable entities, like draggables that aren't selectable,
+      // doesn't start a marquee
+      None if interaction_state.topmost(settings.group).is_some() => {}
+      None => {
+        if !toggle {
+          for (other, _, selected) in selectables.iter() {
+            if selected.is_some() {
+              commands.entity(other).remove::<Selected>();
+            }
+          }
+        }
+        selection_state.marquee = Some(Marquee {
+          start: position,
+          end:   position,
+        });
+      }
+    }
+  }
+
+  if let (Some(marquee), Some(position)) = (&mut selection_state.marquee, position) {
+    marquee.end = position;
+  }
+
+  if released {
+    if let Some(marquee) = selection_state.marquee.take() {
+      let rect = marquee.rect();
+      for (entity, global_transform, _) in selectables.iter() {
+        if rect.contains(global_transform.translation().truncate()) {
+          commands.entity(entity).insert(Selected);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+
+/// The area of an interactable entity that reacts to the cursor.
+/// All coordinates are in the local space of the entity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+  /// Axis aligned box between a minimum and a maximum corner
+  Aabb {
+    min: Vec2,
+    max: Vec2,
+  },
+  Circle {
+    center: Vec2,
+    radius: f32,
+  },
+  /// Every point within `radius` of the line segment from `a` to `b`
+  Capsule {
+    a:      Vec2,
+    b:      Vec2,
+    radius: f32,
+  },
+  /// Convex polygon, the points may be given in either winding order
+  ConvexPolygon(Vec<Vec2>),
+  /// Any simple polygon, tested with the even-odd rule
+  ConcavePolygon(Vec<Vec2>),
+}
+
+impl Default for Shape {
+  fn default() -> Self {
+    Self::Aabb {
+      min: Vec2::default(),
+      max: Vec2::default(),
+    }
+  }
+}
+
+impl From<(Vec2, Vec2)> for Shape {
+  fn from((min, max): (Vec2, Vec2)) -> Self {
+    Self::Aabb { min, max }
+  }
+}
+
+impl Shape {
+  /// Box of the given size centered on the origin
+  pub fn rect(size: Vec2) -> Self {
+    Self::Aabb {
+      min: -size / 2.,
+      max: size / 2.,
+    }
+  }
+
+  /// Circle of the given radius centered on the origin
+  pub fn circle(radius: f32) -> Self {
+    Self::Circle {
+      center: Vec2::ZERO,
+      radius,
+    }
+  }
+
+  /// Whether the point, given in local space, lies within the shape
+  pub fn contains(&self, point: Vec2) -> bool {
+    match self {
+      Shape::Aabb { min, max } => {
+        (min.x..max.x).contains(&point.x) && (min.y..max.y).contains(&point.y)
+      }
+      Shape::Circle { center, radius } => point.distance_squared(*center) <= radius * radius,
+      Shape::Capsule { a, b, radius } => {
+        distance_squared_to_segment(point, *a, *b) <= radius * radius
+      }
+      Shape::ConvexPolygon(points) => convex_polygon_contains(points, point),
+      Shape::ConcavePolygon(points) => concave_polygon_contains(points, point),
+    }
+  }
+
+  /// Closed outline of the shape, curves are approximated with
+  /// the given number of segments per full circle.
+  pub fn outline(&self, segments: usize) -> Vec<Vec2> {
+    match self {
+      Shape::Aabb { min, max } => vec![
+        Vec2::new(min.x, min.y),
+        Vec2::new(max.x, min.y),
+        Vec2::new(max.x, max.y),
+        Vec2::new(min.x, max.y),
+      ],
+      Shape::Circle { center, radius } => {
+        arc(*center, *radius, 0., std::f32::consts::TAU, segments)
+      }
+      Shape::Capsule { a, b, radius } => {
+        let direction = *b - *a;
+        let angle = direction.y.atan2(direction.x);
+        let (quarter, half) = (std::f32::consts::FRAC_PI_2, std::f32::consts::PI);
+        let mut points = arc(*b, *radius, angle - quarter, half, segments / 2);
+        points.extend(arc(*a, *radius, angle + quarter, half, segments / 2));
+        points
+      }
+      Shape::ConvexPolygon(points) | Shape::ConcavePolygon(points) => points.clone(),
+    }
+  }
+}
+
+fn arc(center: Vec2, radius: f32, start: f32, sweep: f32, segments: usize) -> Vec<Vec2> {
+  let segments = segments.max(1);
+  (0..=segments)
+    .map(|i| {
+      let angle = start + sweep * i as f32 / segments as f32;
+      center + Vec2::new(angle.cos(), angle.sin()) * radius
+    })
+    .collect()
+}
+
+fn distance_squared_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+  let segment = b - a;
+  let length_squared = segment.length_squared();
+  if length_squared == 0. {
+    return point.distance_squared(a);
+  }
+  let t = ((point - a).dot(segment) / length_squared).clamp(0., 1.);
+  point.distance_squared(a + segment * t)
+}
+
+fn convex_polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+  if points.len() < 3 {
+    return false;
+  }
+  let mut sign = 0.;
+  for (i, a) in points.iter().enumerate() {
+    let b = points[(i + 1) % points.len()];
+    let cross = (b - *a).perp_dot(point - *a);
+    if cross == 0. {
+      continue;
+    }
+    if sign == 0. {
+      sign = cross.signum();
+    } else if cross.signum() != sign {
+      return false;
+    }
+  }
+  true
+}
+
+fn concave_polygon_contains(points: &[Vec2], point: Vec2) -> bool {
+  if points.len() < 3 {
+    return false;
+  }
+  let mut inside = false;
+  let mut j = points.len() - 1;
+  for i in 0..points.len() {
+    let (a, b) = (points[i], points[j]);
+    if (a.y > point.y) != (b.y > point.y)
+      && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+    {
+      inside = !inside;
+    }
+    j = i;
+  }
+  inside
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square() -> Vec<Vec2> {
+    vec![
+      Vec2::new(0., 0.),
+      Vec2::new(2., 0.),
+      Vec2::new(2., 2.),
+      Vec2::new(0., 2.),
+    ]
+  }
+
+  #[test]
+  fn concave_polygon_excludes_its_notch() {
+    let l_shape = Shape::ConcavePolygon(vec![
+      Vec2::new(0., 0.),
+      Vec2::new(2., 0.),
+      Vec2::new(2., 1.),
+      Vec2::new(1., 1.),
+      Vec2::new(1., 2.),
+      Vec2::new(0., 2.),
+    ]);
+    assert!(l_shape.contains(Vec2::new(0.5, 1.5)));
+    assert!(l_shape.contains(Vec2::new(1.5, 0.5)));
+    assert!(!l_shape.contains(Vec2::new(1.5, 1.5)));
+    assert!(!l_shape.contains(Vec2::new(3., 0.5)));
+  }
+
+  #[test]
+  fn convex_polygon_accepts_either_winding() {
+    let counter_clockwise = square();
+    let clockwise: Vec<Vec2> = square().into_iter().rev().collect();
+    for points in [counter_clockwise, clockwise] {
+      let shape = Shape::ConvexPolygon(points);
+      assert!(shape.contains(Vec2::new(1., 1.)));
+      assert!(!shape.contains(Vec2::new(3., 1.)));
+      assert!(!shape.contains(Vec2::new(1., -1.)));
+    }
+  }
+
+  #[test]
+  fn convex_polygon_excludes_edge_extensions() {
+    let shape = Shape::ConvexPolygon(square());
+    assert!(!shape.contains(Vec2::new(3., 0.)));
+    assert!(!shape.contains(Vec2::new(-1., 2.)));
+    assert!(!shape.contains(Vec2::new(0., 3.)));
+  }
+
+  #[test]
+  fn capsule_includes_its_rounded_ends() {
+    let capsule = Shape::Capsule {
+      a:      Vec2::new(0., 0.),
+      b:      Vec2::new(4., 0.),
+      radius: 1.,
+    };
+    assert!(capsule.contains(Vec2::new(2., 0.9)));
+    assert!(capsule.contains(Vec2::new(-0.9, 0.)));
+    assert!(capsule.contains(Vec2::new(4.9, 0.)));
+    assert!(capsule.contains(Vec2::new(4.5, 0.5)));
+    assert!(!capsule.contains(Vec2::new(-1.1, 0.)));
+    assert!(!capsule.contains(Vec2::new(5.1, 0.)));
+    assert!(!capsule.contains(Vec2::new(4.7, 0.8)));
+  }
+
+  #[test]
+  fn degenerate_polygons_contain_nothing() {
+    let points = [
+      vec![],
+      vec![Vec2::ZERO],
+      vec![Vec2::ZERO, Vec2::new(2., 0.)],
+    ];
+    for points in points {
+      assert!(!Shape::ConvexPolygon(points.clone()).contains(Vec2::new(1., 0.)));
+      assert!(!Shape::ConcavePolygon(points).contains(Vec2::new(1., 0.)));
+    }
+  }
+}
@@ -0,0 +1,186 @@
+use bevy::{ecs::system::SystemParam, prelude::*, render::camera::RenderTarget};
+
+use super::{Group, InteractionSource};
+
+/// Label of the system moving the virtual cursors, which runs in the pre update stage.
+/// Systems reading virtual cursor presses before the update stage run after it.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub struct VirtualCursorSystem;
+
+/// Attach a virtual cursor to an interaction source to interact with
+/// the keyboard or a gamepad instead of the mouse.
+/// The interaction source will use the virtual cursor in place of the mouse cursor.
+#[derive(Component)]
+pub struct VirtualCursor {
+  /// Position in window coordinates, measured from the bottom left
+  pub position:      Vec2,
+  /// Speed in logical pixels per second when movement starts
+  pub speed:         f32,
+  /// Increase of the speed per second while the cursor keeps moving
+  pub acceleration:  f32,
+  pub max_speed:     f32,
+  /// Stick deflection below which the stick is ignored
+  pub dead_zone:     f32,
+  /// The gamepad controlling the cursor, any gamepad when None
+  pub gamepad:       Option<Gamepad>,
+  /// Keys moving the cursor up, down, left and right
+  pub move_keys:     [KeyCode; 4],
+  pub press_keys:    Vec<KeyCode>,
+  pub press_buttons: Vec<GamepadButtonType>,
+  current_speed:     f32,
+  pressed:           bool,
+  just_pressed:      bool,
+  just_released:     bool,
+}
+
+impl Default for VirtualCursor {
+  fn default() -> Self {
+    Self {
+      position:      Vec2::ZERO,
+      speed:         200.,
+      acceleration:  400.,
+      max_speed:     800.,
+      dead_zone:     0.2,
+      gamepad:       None,
+      move_keys:     [KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right],
+      press_keys:    vec![KeyCode::Space, KeyCode::Return],
+      press_buttons: vec![GamepadButtonType::South],
+      current_speed: 0.,
+      pressed:       false,
+      just_pressed:  false,
+      just_released: false,
+    }
+  }
+}
+
+impl VirtualCursor {
+  pub fn pressed(&self) -> bool {
+    self.pressed
+  }
+  pub fn just_pressed(&self) -> bool {
+    self.just_pressed
+  }
+  pub fn just_released(&self) -> bool {
+    self.just_released
+  }
+}
+
+/// The interaction sources with a virtual cursor.
+/// Presses of a virtual cursor only apply to the groups of its interaction source,
+/// and the mouse buttons don't apply to those groups.
+#[derive(SystemParam)]
+pub struct VirtualCursors<'w, 's> {
+  sources: Query<'w, 's, (Entity, &'static InteractionSource, &'static VirtualCursor)>,
+}
+
+impl<'w, 's> VirtualCursors<'w, 's> {
+  /// The virtual cursor of the interaction source, if it has one
+  pub fn get(&self, source: Entity) -> Option<&VirtualCursor> {
+    self.sources.get(source).ok().map(|(_, _, cursor)| cursor)
+  }
+
+  /// The interaction source using a virtual cursor for the group, if any
+  pub fn get_group(&self, group: Group) -> Option<(Entity, &VirtualCursor)> {
+    self
+      .sources
+      .iter()
+      .find(|(_, source, _)| source.groups.contains(&group))
+      .map(|(entity, _, cursor)| (entity, cursor))
+  }
+
+  /// The interaction sources whose virtual cursor was just pressed
+  pub fn just_pressed(&self) -> impl Iterator<Item = Entity> + '_ {
+    self
+      .sources
+      .iter()
+      .filter(|(_, _, cursor)| cursor.just_pressed())
+      .map(|(entity, _, _)| entity)
+  }
+}
+
+/// This system moves the virtual cursors and updates their press state
+pub fn virtual_cursor_system(
+  time: Res<Time>,
+  windows: Res<Windows>,
+  keyboard_input: Res<Input<KeyCode>>,
+  gamepads: Res<Gamepads>,
+  gamepad_buttons: Res<Input<GamepadButton>>,
+  gamepad_axes: Res<Axis<GamepadAxis>>,
+  mut cursors: Query<(&mut VirtualCursor, Option<&Camera>)>,
+) {
+  for (mut cursor, camera) in cursors.iter_mut() {
+    let cursor = &mut *cursor;
+    let controlling: Vec<Gamepad> = gamepads
+      .iter()
+      .filter(|gamepad| cursor.gamepad.is_none_or(|g| g == *gamepad))
+      .collect();
+
+    let mut direction = Vec2::ZERO;
+    let [up, down, left, right] = cursor.move_keys;
+    for (key, step) in [
+      (up, Vec2::Y),
+      (down, -Vec2::Y),
+      (left, -Vec2::X),
+      (right, Vec2::X),
+    ] {
+      if keyboard_input.pressed(key) {
+        direction += step;
+      }
+    }
+    for gamepad in controlling.iter() {
+      let dpad = [
+        (GamepadButtonType::DPadUp, Vec2::Y),
+        (GamepadButtonType::DPadDown, -Vec2::Y),
+        (GamepadButtonType::DPadLeft, -Vec2::X),
+        (GamepadButtonType::DPadRight, Vec2::X),
+      ];
+      for (button_type, step) in dpad {
+        if gamepad_buttons.pressed(GamepadButton::new(*gamepad, button_type)) {
+          direction += step;
+        }
+      }
+      let stick = Vec2::new(
+        gamepad_axes
+          .get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickX))
+          .unwrap_or_default(),
+        gamepad_axes
+          .get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickY))
+          .unwrap_or_default(),
+      );
+      if stick.length() > cursor.dead_zone {
+        direction += stick;
+      }
+    }
+    let direction = direction.clamp_length_max(1.);
+
+    if direction == Vec2::ZERO {
+      cursor.current_speed = cursor.speed;
+    } else {
+      cursor.current_speed = (cursor.current_speed.max(cursor.speed)
+        + cursor.acceleration * time.delta_seconds())
+      .min(cursor.max_speed);
+      cursor.position += direction * cursor.current_speed * time.delta_seconds();
+    }
+
+    let window = match camera.map(|camera| &camera.target) {
+      Some(RenderTarget::Window(window_id)) => windows.get(*window_id),
+      _ => windows.get_primary(),
+    };
+    if let Some(window) = window {
+      cursor.position = cursor
+        .position
+        .clamp(Vec2::ZERO, Vec2::new(window.width(), window.height()));
+    }
+
+    let pressed = keyboard_input.any_pressed(cursor.press_keys.iter().copied())
+      || controlling.iter().any(|gamepad| {
+        cursor
+          .press_buttons
+          .iter()
+          .any(|button_type| gamepad_buttons.pressed(GamepadButton::new(*gamepad, *button_type)))
+      });
+    cursor.just_pressed = pressed && !cursor.pressed;
+    cursor.just_released = !pressed && cursor.pressed;
+    cursor.pressed = pressed;
+  }
+}